@@ -0,0 +1,58 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{to_binary, Addr, Binary, Coin, CosmosMsg, StdResult, WasmMsg};
+
+// interface for bot contracts, bot authors depend on this crate instead of the whole contract
+
+// sent to a registered bot once the beacon of its challenge is in
+// the host committed to their move before the beacon was published, so the host can't
+// simulate the answer, the bot should mix `randomness` into its move
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BotChallengeMsg {
+    pub host: Addr,
+    // matched from the bot's own balance in the rps contract
+    pub wager: Option<Coin>,
+    // randomness of the drand beacon the challenge waited for
+    pub randomness: Binary,
+}
+
+impl BotChallengeMsg {
+    // serializes the message so it can be sent to a bot
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = BotExecuteMsg::RpsChallenge(self);
+        to_binary(&msg)
+    }
+
+    // creates a cosmos_msg sending this struct to the named bot
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+// bots need to accept this variant in their ExecuteMsg
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BotExecuteMsg {
+    RpsChallenge(BotChallengeMsg),
+}
+
+// same json as the contract's GameMove
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub enum BotMove {
+    Rock {},
+    Paper {},
+    Scissors {},
+}
+
+// bots answer by setting this as the data of their Response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BotMoveResponse {
+    pub bot_move: BotMove,
+}
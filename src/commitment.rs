@@ -0,0 +1,32 @@
+use cosmwasm_std::Binary;
+use sha2::{Digest, Sha256};
+
+use crate::state::GameMove;
+
+// commit-reveal for moves, so that the other player can't see the move before playing theirs
+// commitment = sha256(move name + salt), e.g. sha256("rock" + "random secret")
+// the salt should be long and random, otherwise the three possible moves are easy to try out
+
+pub fn move_commitment(game_move: &GameMove, salt: &str) -> Binary {
+    let mut hasher = Sha256::new();
+    hasher.update(game_move.as_str().as_bytes());
+    hasher.update(salt.as_bytes());
+    Binary::from(hasher.finalize().as_slice())
+}
+
+pub fn verify_move(commitment: &Binary, game_move: &GameMove, salt: &str) -> bool {
+    &move_commitment(game_move, salt) == commitment
+}
+
+// both moves of a minus one round, sha256(first move name + second move name + salt)
+pub fn moves_commitment(moves: &(GameMove, GameMove), salt: &str) -> Binary {
+    let mut hasher = Sha256::new();
+    hasher.update(moves.0.as_str().as_bytes());
+    hasher.update(moves.1.as_str().as_bytes());
+    hasher.update(salt.as_bytes());
+    Binary::from(hasher.finalize().as_slice())
+}
+
+pub fn verify_moves(commitment: &Binary, moves: &(GameMove, GameMove), salt: &str) -> bool {
+    &moves_commitment(moves, salt) == commitment
+}
@@ -1,16 +1,50 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdError, StdResult,
+    attr, coin, from_binary, to_binary, Addr, Attribute, BankMsg, Binary, Coin, CosmosMsg, Deps,
+    DepsMut, Empty, Env, MessageInfo, Order, Reply, Response, StdError, StdResult, Storage, SubMsg,
+    Uint128,
 };
 use cw2::set_contract_version;
 
 use cw_controllers::{Admin, AdminError, AdminResponse, HookError};
-use cw_utils::maybe_addr;
+use cw_storage_plus::Bound;
+use cw_utils::{
+    maybe_addr, nonpayable, one_coin, parse_reply_execute_data, Duration, Expiration, PaymentError,
+};
+use rps_bot::{BotChallengeMsg, BotMoveResponse};
+use sha2::{Digest, Sha256};
 
+use crate::commitment::{move_commitment, verify_move, verify_moves};
+use crate::cw721::{transfer_nft_msg, Cw721ReceiveMsg};
+use crate::drand::{beacon_randomness, next_round, verify_beacon};
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, GamesListResponse, InstantiateMsg, QueryMsg};
-use crate::state::{games, Game, GameMove, GameResult, ADMIN, BLACKLIST};
+use crate::hooks::RpsHookMsg;
+use crate::msg::{
+    AllowlistResponse, AuditLogEntry, AuditLogResponse, BalanceResponse, BanInfo, BetPoolResponse,
+    BetResponse, BlacklistResponse, BlockListResponse, BotsResponse, ClaimsResponse, ExecuteMsg,
+    GamesListResponse, HooksResponse, HouseResponse, InstantiateMsg, MigrateMsg,
+    NftCollectionsResponse, NftReceiveMsg, OperatorInfo, OperatorsResponse, QueryMsg,
+    RolesResponse, RoyaleMovesResponse, SignedMove, TreasuryResponse,
+};
+use crate::signature::{
+    derive_address, verify_signature, RelayedMsg, SignedMovePayload, RELAYED_MSG_DOMAIN,
+    SIGNED_MOVE_DOMAIN,
+};
+use crate::solvency::solvency_report;
+use crate::state::{
+    audit_log, games, Approval, AuditEntry, BanRecord, BeaconConfig, BeaconRound, Bet, BetPool,
+    Config, CounterOffer, CounterOfferStatus, EscrowedNft, Game, GameMove, GameResult, GameStatus,
+    GameTerms, HouseConfig, HouseGame, KeyType, LegacyGame, MinusOne, MinusOneHand, MinusOnePhase,
+    Payout, PendingAdmin, Role, Royale, RoyaleMove, RoyaleStatus, Ruleset, Score, SigningKey,
+    StandingStrategy, Strategy, TeamGame, TeamGameStatus, TeamVote, TieBreak, TimeoutLimits, ADMIN,
+    ALLOWLIST, AUDIT_SEQ, BALANCES, BANKROLL, BETS, BET_POOLS, BLACKLIST, BLOCKS, BOTS, CLAIMS,
+    CONFIG, DEFAULT_GAME_TIMEOUT, HOOKS, HOUSE_GAMES, LEGACY_BLACKLIST, LEGACY_GAMES, MAX_BEST_OF,
+    MAX_BETTORS, MAX_COUNTER_OFFERS, MAX_ROYALE_PLAYERS, MAX_TEAM_SIZE, MIN_ROYALE_PLAYERS,
+    NFT_CLAIMS, NFT_COLLECTIONS, OPERATORS, PAYOUT_SEQ, PENDING_ADMIN, PENDING_BOT_GAMES,
+    PENDING_PAYOUTS, RELAY_NONCES, ROLES, ROYALES, ROYALE_MOVES, ROYALE_SEQ, SIGNED_GAME_NONCES,
+    SIGNING_KEYS, STRATEGIES, TEAM_GAMES, TEAM_GAME_SEQ, TREASURY,
+};
 
 const CONTRACT_NAME: &str = "crates.io:rps";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -24,6 +58,11 @@ pub fn instantiate(
 ) -> Result<Response, AdminError> {
     let maybe_admin = maybe_addr(deps.api, Some(msg.admin.to_string()))?;
     ADMIN.set(deps.branch(), maybe_admin)?;
+    let config = Config {
+        allowlist_enabled: msg.allowlist_enabled,
+        ..Config::default()
+    };
+    CONFIG.save(deps.storage, &config)?;
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     Ok(Response::new()
@@ -31,220 +70,3965 @@ pub fn instantiate(
         .add_attribute("owner", info.sender))
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    // older versions kept a plain list of banned addresses, those bans stay permanent
+    let legacy = LEGACY_BLACKLIST.may_load(deps.storage)?.unwrap_or_default();
+    let banned_by = ADMIN
+        .get(deps.as_ref())?
+        .unwrap_or_else(|| env.contract.address.clone());
+    for addr in &legacy {
+        if BLACKLIST.has(deps.storage, addr) {
+            continue;
+        }
+        let ban = BanRecord {
+            reason: None,
+            banned_by: banned_by.clone(),
+            banned_at: env.block.time,
+            expires: None,
+        };
+        BLACKLIST.save(deps.storage, addr, &ban)?;
+    }
+    LEGACY_BLACKLIST.remove(deps.storage);
+
+    // games started before the upgrade are plain games nobody staked anything on,
+    // they carry on as such and get the default timeout from now on
+    // (by raw key, games().keys() skips entries whose value doesn't deserialize)
+    let legacy_games: Vec<LegacyGame> = LEGACY_GAMES
+        .keys_raw(deps.storage, None, None, Order::Ascending)
+        .filter_map(|key| LEGACY_GAMES.load(deps.storage, &key).ok())
+        .collect();
+    for legacy in &legacy_games {
+        let game = Game {
+            host: legacy.host.clone(),
+            opponent: legacy.opponent.clone(),
+            status: GameStatus::Proposed,
+            host_move: Some(legacy.host_move.clone()),
+            host_commitment: None,
+            opponent_move: None,
+            result: None,
+            wager: None,
+            host_nft: None,
+            opponent_collection: None,
+            opponent_nft: None,
+            opponent_nft_accepted: false,
+            bond: None,
+            host_hand: None,
+            opponent_hand: None,
+            minus_one: None,
+            beacon: None,
+            ruleset: Ruleset::Classic {},
+            best_of: 1,
+            score: Score::default(),
+            timeout: DEFAULT_GAME_TIMEOUT,
+            counter_offers: vec![],
+            expires: DEFAULT_GAME_TIMEOUT.after(&env.block),
+        };
+        // index keys only depend on host and opponent, so passing the new game as the old one
+        // clears the index entries the legacy game left behind
+        games().replace(
+            deps.storage,
+            generate_key_for_game(&game),
+            Some(&game),
+            Some(&game),
+        )?;
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("migrated_bans", legacy.len().to_string())
+        .add_attribute("migrated_games", legacy_games.len().to_string()))
+}
+
 type Res<T> = Result<T, Box<dyn std::error::Error>>;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(deps: DepsMut, _env: Env, info: MessageInfo, msg: ExecuteMsg) -> Res<Response> {
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> Res<Response> {
     match msg {
         ExecuteMsg::StartGame {
             opponent,
             host_move,
-        } => Ok(try_startgame(deps, info, opponent, host_move)?),
+        } => Ok(try_startgame(deps, env, info, opponent, host_move)?),
         ExecuteMsg::EndGame {
             host,
             opponent_move,
-        } => Ok(try_endgame(deps, info, host, opponent_move)?),
-        ExecuteMsg::UpdateAdmin { admin } => Ok(try_updateadmin(deps, info, admin)?),
-        ExecuteMsg::AddToBlacklist { addr } => Ok(try_addtoblacklist(ADMIN, deps, info, addr)?),
+        } => Ok(try_endgame(deps, env, info, host, opponent_move)?),
+        ExecuteMsg::ProposeGame { opponent, terms } => {
+            Ok(try_proposegame(deps, env, info, opponent, terms)?)
+        }
+        ExecuteMsg::AcceptChallenge { host } => Ok(try_acceptchallenge(deps, env, info, host)?),
+        ExecuteMsg::DeclineChallenge { host } => Ok(try_declinechallenge(deps, info, host)?),
+        ExecuteMsg::CounterChallenge { host, terms } => {
+            Ok(try_counterchallenge(deps, env, info, host, terms)?)
+        }
+        ExecuteMsg::AcceptNft { opponent } => Ok(try_acceptnft(deps, env, info, opponent)?),
+        ExecuteMsg::RejectNft { opponent } => Ok(try_rejectnft(deps, env, info, opponent)?),
+        ExecuteMsg::AcceptCounter { opponent } => Ok(try_acceptcounter(deps, env, info, opponent)?),
+        ExecuteMsg::RejectCounter { opponent } => Ok(try_rejectcounter(deps, env, info, opponent)?),
+        ExecuteMsg::CommitMove {
+            opponent,
+            commitment,
+        } => Ok(try_commitmove(deps, env, info, opponent, commitment)?),
+        ExecuteMsg::RevealMove {
+            opponent,
+            host_move,
+            salt,
+        } => Ok(try_revealmove(deps, env, info, opponent, host_move, salt)?),
+        ExecuteMsg::CommitChallenge {
+            opponent,
+            commitment,
+            wager,
+        } => Ok(try_commitchallenge(
+            deps, env, info, opponent, commitment, wager,
+        )?),
+        ExecuteMsg::SubmitChallengeBeacon {
+            host,
+            opponent,
+            round,
+            previous_signature,
+            signature,
+        } => Ok(try_submitchallengebeacon(
+            deps,
+            env,
+            info,
+            host,
+            opponent,
+            round,
+            previous_signature,
+            signature,
+        )?),
+        ExecuteMsg::CommitMinusOne {
+            host,
+            opponent,
+            commitment,
+        } => Ok(try_commitminusone(
+            deps, env, info, host, opponent, commitment,
+        )?),
+        ExecuteMsg::RevealMinusOne {
+            host,
+            opponent,
+            moves,
+            salt,
+        } => Ok(try_revealminusone(
+            deps, env, info, host, opponent, moves, salt,
+        )?),
+        ExecuteMsg::CommitWithdrawal {
+            host,
+            opponent,
+            commitment,
+        } => Ok(try_commitwithdrawal(
+            deps, env, info, host, opponent, commitment,
+        )?),
+        ExecuteMsg::RevealWithdrawal {
+            host,
+            opponent,
+            withdrawn,
+            salt,
+        } => Ok(try_revealwithdrawal(
+            deps, env, info, host, opponent, withdrawn, salt,
+        )?),
+        ExecuteMsg::Deposit {} => Ok(try_deposit(deps, info)?),
+        ExecuteMsg::Withdraw { amount } => Ok(try_withdraw(deps, info, amount)?),
+        ExecuteMsg::Claim {} => Ok(try_claim(deps, info)?),
+        ExecuteMsg::SetStrategy {
+            strategy,
+            max_wager,
+            max_answers,
+        } => Ok(try_setstrategy(
+            deps,
+            info,
+            strategy,
+            max_wager,
+            max_answers,
+        )?),
+        ExecuteMsg::ClearStrategy {} => Ok(try_clearstrategy(deps, info)?),
+        ExecuteMsg::SetSigningKey { key_type, pubkey } => {
+            Ok(try_setsigningkey(deps, info, key_type, pubkey)?)
+        }
+        ExecuteMsg::RemoveSigningKey {} => Ok(try_removesigningkey(deps, info)?),
+        ExecuteMsg::PlaySigned {
+            host,
+            opponent,
+            wager,
+            expires,
+            host_move,
+            opponent_move,
+        } => Ok(try_playsigned(
+            deps,
+            env,
+            info,
+            host,
+            opponent,
+            wager,
+            expires,
+            host_move,
+            opponent_move,
+        )?),
+        ExecuteMsg::ExecuteSigned {
+            payload,
+            signature,
+            pubkey,
+        } => try_executesigned(deps, env, info, payload, signature, pubkey),
+        ExecuteMsg::ApproveOperator {
+            operator,
+            max_wager,
+            expires,
+        } => Ok(try_approveoperator(
+            deps, info, operator, max_wager, expires,
+        )?),
+        ExecuteMsg::RevokeOperator { operator } => Ok(try_revokeoperator(deps, info, operator)?),
+        ExecuteMsg::ExecuteAs { player, msg } => try_executeas(deps, env, info, player, *msg),
+        ExecuteMsg::BlockPlayer { addr } => Ok(try_blockplayer(deps, info, addr)?),
+        ExecuteMsg::UnblockPlayer { addr } => Ok(try_unblockplayer(deps, info, addr)?),
+        ExecuteMsg::ProposeAdmin { admin, expires } => {
+            Ok(try_proposeadmin(deps, env, info, admin, expires)?)
+        }
+        ExecuteMsg::AcceptAdmin {} => Ok(try_acceptadmin(deps, env, info)?),
+        ExecuteMsg::CancelAdminProposal {} => Ok(try_canceladminproposal(deps, env, info)?),
+        ExecuteMsg::RenounceAdmin {} => Ok(try_renounceadmin(deps, env, info)?),
+        ExecuteMsg::AddToBlacklist {
+            addr,
+            reason,
+            expires,
+        } => Ok(try_addtoblacklist(deps, env, info, addr, reason, expires)?),
         ExecuteMsg::RemoveFromBlacklist { addr } => {
-            Ok(try_removefromblacklist(ADMIN, deps, info, addr)?)
+            Ok(try_removefromblacklist(deps, env, info, addr)?)
+        }
+        ExecuteMsg::GrantRole { role, addr } => Ok(try_grantrole(deps, env, info, role, addr)?),
+        ExecuteMsg::RevokeRole { role, addr } => Ok(try_revokerole(deps, env, info, role, addr)?),
+        ExecuteMsg::CancelGame { host, opponent } => {
+            Ok(try_cancelgame(deps, env, info, host, opponent)?)
+        }
+        ExecuteMsg::UpdateFee { fee_bps } => Ok(try_updatefee(deps, env, info, fee_bps)?),
+        ExecuteMsg::WithdrawFees { amount } => Ok(try_withdrawfees(deps, env, info, amount)?),
+        ExecuteMsg::UpdateConfig {
+            allowlist_enabled,
+            game_bond,
+            max_open_games,
+            max_fee_bps,
+            treasury,
+            timeout_limits,
+        } => Ok(try_updateconfig(
+            deps,
+            env,
+            info,
+            allowlist_enabled,
+            game_bond,
+            max_open_games,
+            max_fee_bps,
+            treasury,
+            timeout_limits,
+        )?),
+        ExecuteMsg::AddToAllowlist { addr } => Ok(try_addtoallowlist(deps, env, info, addr)?),
+        ExecuteMsg::RemoveFromAllowlist { addr } => {
+            Ok(try_removefromallowlist(deps, env, info, addr)?)
+        }
+        ExecuteMsg::AddHook { addr } => Ok(try_addhook(ADMIN, deps, env, info, addr)?),
+        ExecuteMsg::RemoveHook { addr } => Ok(try_removehook(ADMIN, deps, env, info, addr)?),
+        ExecuteMsg::RegisterBot { addr } => Ok(try_registerbot(deps, env, info, addr)?),
+        ExecuteMsg::RemoveBot { addr } => Ok(try_removebot(deps, env, info, addr)?),
+        ExecuteMsg::AddNftCollection { addr } => Ok(try_addnftcollection(deps, env, info, addr)?),
+        ExecuteMsg::RemoveNftCollection { addr } => {
+            Ok(try_removenftcollection(deps, env, info, addr)?)
+        }
+        ExecuteMsg::ExpireGame { host, opponent } => {
+            Ok(try_expiregame(deps, env, info, host, opponent)?)
+        }
+        ExecuteMsg::ReceiveNft(msg) => Ok(try_receivenft(deps, env, info, msg)?),
+        ExecuteMsg::CreateRoyale {
+            entry_fee,
+            max_players,
+            phase_timeout,
+        } => Ok(try_createroyale(
+            deps,
+            env,
+            info,
+            entry_fee,
+            max_players,
+            phase_timeout,
+        )?),
+        ExecuteMsg::JoinRoyale { id } => Ok(try_joinroyale(deps, env, info, id)?),
+        ExecuteMsg::StartRoyale { id } => Ok(try_startroyale(deps, env, info, id)?),
+        ExecuteMsg::CommitRoyaleMove { id, commitment } => {
+            Ok(try_commitroyalemove(deps, env, info, id, commitment)?)
+        }
+        ExecuteMsg::RevealRoyaleMove {
+            id,
+            game_move,
+            salt,
+        } => Ok(try_revealroyalemove(deps, env, info, id, game_move, salt)?),
+        ExecuteMsg::AdvanceRoyale { id } => Ok(try_advanceroyale(deps, env, info, id)?),
+        ExecuteMsg::CreateTeamGame {
+            host_team,
+            opponent_team,
+            wager,
+            tie_break,
+            phase_timeout,
+        } => Ok(try_createteamgame(
+            deps,
+            env,
+            info,
+            host_team,
+            opponent_team,
+            wager,
+            tie_break,
+            phase_timeout,
+        )?),
+        ExecuteMsg::CommitTeamVote { id, commitment } => {
+            Ok(try_committeamvote(deps, env, info, id, commitment)?)
+        }
+        ExecuteMsg::RevealTeamVote {
+            id,
+            game_move,
+            salt,
+        } => Ok(try_revealteamvote(deps, env, info, id, game_move, salt)?),
+        ExecuteMsg::AdvanceTeamGame { id } => Ok(try_advanceteamgame(deps, env, info, id)?),
+        ExecuteMsg::UpdateBeacon { beacon } => Ok(try_updatebeacon(deps, env, info, beacon)?),
+        ExecuteMsg::UpdateHouse { house } => Ok(try_updatehouse(deps, env, info, house)?),
+        ExecuteMsg::FundBankroll {} => Ok(try_fundbankroll(deps, env, info)?),
+        ExecuteMsg::WithdrawBankroll { amount, recipient } => {
+            Ok(try_withdrawbankroll(deps, env, info, amount, recipient)?)
+        }
+        ExecuteMsg::PlayHouse { player_move, wager } => {
+            Ok(try_playhouse(deps, env, info, player_move, wager)?)
+        }
+        ExecuteMsg::SubmitBeacon {
+            player,
+            round,
+            previous_signature,
+            signature,
+        } => Ok(try_submitbeacon(
+            deps,
+            info,
+            player,
+            round,
+            previous_signature,
+            signature,
+        )?),
+        ExecuteMsg::ExpireHouseGame { player } => Ok(try_expirehousegame(deps, env, info, player)?),
+        ExecuteMsg::PlaceBet {
+            host,
+            opponent,
+            outcome,
+        } => Ok(try_placebet(deps, env, info, host, opponent, outcome)?),
+    }
+}
+
+pub fn try_startgame(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    opponent: Addr,
+    host_move: GameMove,
+) -> Result<Response, ContractError> {
+    // the host move would be public before the bot answers
+    if BOTS.has(deps.storage, &opponent) {
+        return Err(ContractError::BotNeedsCommitment {});
+    }
+    // whatever was sent along tops up the balance the bond is taken from
+    deposit_funds(deps.storage, &info.sender, &info.funds)?;
+    // host move is stored in the clear, so there's no stake, wagers need a challenge
+    let terms = GameTerms {
+        wager: None,
+        ruleset: Ruleset::Classic {},
+        best_of: 1,
+        timeout: None,
+    };
+    let res = start_game(
+        deps,
+        env,
+        info.sender,
+        opponent,
+        Some(host_move),
+        terms,
+        None,
+        None,
+    )?;
+    Ok(res.add_attribute("method", "try_startgame"))
+}
+
+// whether player's standing strategy covers a challenge, taking it on counts against max_answers
+fn take_standing_challenge(
+    storage: &mut dyn Storage,
+    env: &Env,
+    player: &Addr,
+    wager: &Option<Coin>,
+) -> Result<bool, ContractError> {
+    let mut standing = match STRATEGIES.may_load(storage, player)? {
+        Some(standing) => standing,
+        None => return Ok(false),
+    };
+    if standing.accepted >= standing.max_answers {
+        return Ok(false);
+    }
+    if let Some(wager) = wager {
+        let within_limit = match &standing.max_wager {
+            Some(max) => max.denom == wager.denom && max.amount >= wager.amount,
+            None => false,
+        };
+        let balance = BALANCES
+            .may_load(storage, (player, &wager.denom))?
+            .unwrap_or_default();
+        if !within_limit || balance < wager.amount {
+            return Ok(false);
+        }
+    }
+    // player couldn't answer by hand either
+    if assert_allowlisted(storage, player).is_err()
+        || assert_not_blacklisted(storage, env, player).is_err()
+    {
+        return Ok(false);
+    }
+
+    standing.accepted += 1;
+    STRATEGIES.save(storage, player, &standing)?;
+    Ok(true)
+}
+
+// the move player's standing strategy answers host with, if they still have one
+fn standing_answer(
+    storage: &mut dyn Storage,
+    player: &Addr,
+    host: &Addr,
+    randomness: &[u8],
+) -> StdResult<Option<GameMove>> {
+    let mut standing = match STRATEGIES.may_load(storage, player)? {
+        Some(standing) => standing,
+        None => return Ok(None),
+    };
+    let answer = strategy_move(&standing.strategy, host, standing.answered, randomness);
+    standing.answered += 1;
+    STRATEGIES.save(storage, player, &standing)?;
+    Ok(Some(answer))
+}
+
+// randomness comes from a beacon published after the host committed, so the answer
+// can't be known in advance; the host and the answer count tell challenges on one round apart
+fn strategy_move(strategy: &Strategy, host: &Addr, answered: u64, randomness: &[u8]) -> GameMove {
+    match strategy {
+        Strategy::Weighted {
+            rock,
+            paper,
+            scissors,
+        } => {
+            let mut hasher = Sha256::new();
+            hasher.update(randomness);
+            hasher.update(host.as_bytes());
+            hasher.update(answered.to_be_bytes());
+            let mut value = [0u8; 8];
+            value.copy_from_slice(&hasher.finalize()[..8]);
+            let total = *rock as u64 + *paper as u64 + *scissors as u64;
+            let pick = u64::from_be_bytes(value) % total;
+            if pick < *rock as u64 {
+                GameMove::Rock {}
+            } else if pick < *rock as u64 + *paper as u64 {
+                GameMove::Paper {}
+            } else {
+                GameMove::Scissors {}
+            }
+        }
+    }
+}
+
+pub fn try_setstrategy(
+    deps: DepsMut,
+    info: MessageInfo,
+    strategy: Strategy,
+    max_wager: Option<Coin>,
+    max_answers: u64,
+) -> Result<Response, ContractError> {
+    // whatever was sent along tops up the balance the answers are played from
+    deposit_funds(deps.storage, &info.sender, &info.funds)?;
+    // a move that's never played would leave the host a move that can't lose
+    let valid = match &strategy {
+        Strategy::Weighted {
+            rock,
+            paper,
+            scissors,
+        } => *rock > 0 && *paper > 0 && *scissors > 0,
+    };
+    if !valid {
+        return Err(ContractError::InvalidStrategy {});
+    }
+
+    // a new strategy starts from its first move again
+    let standing = StandingStrategy {
+        strategy,
+        max_wager: max_wager.filter(|max| !max.amount.is_zero()),
+        max_answers,
+        accepted: 0,
+        answered: 0,
+    };
+    STRATEGIES.save(deps.storage, &info.sender, &standing)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_setstrategy")
+        .add_attribute("player", info.sender))
+}
+
+pub fn try_clearstrategy(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    if !STRATEGIES.has(deps.storage, &info.sender) {
+        return Err(ContractError::NoStrategy {});
+    }
+    STRATEGIES.remove(deps.storage, &info.sender);
+
+    Ok(Response::new()
+        .add_attribute("method", "try_clearstrategy")
+        .add_attribute("player", info.sender))
+}
+
+pub fn try_setsigningkey(
+    deps: DepsMut,
+    info: MessageInfo,
+    key_type: KeyType,
+    pubkey: Binary,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let valid = match key_type {
+        KeyType::Secp256k1 => pubkey.len() == 33 || pubkey.len() == 65,
+        KeyType::Ed25519 => pubkey.len() == 32,
+    };
+    if !valid {
+        return Err(ContractError::InvalidSigningKey {});
+    }
+    SIGNING_KEYS.save(deps.storage, &info.sender, &SigningKey { key_type, pubkey })?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_setsigningkey")
+        .add_attribute("player", info.sender))
+}
+
+pub fn try_removesigningkey(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    if !SIGNING_KEYS.has(deps.storage, &info.sender) {
+        return Err(ContractError::NoSigningKey { addr: info.sender });
+    }
+    SIGNING_KEYS.remove(deps.storage, &info.sender);
+
+    Ok(Response::new()
+        .add_attribute("method", "try_removesigningkey")
+        .add_attribute("player", info.sender))
+}
+
+// starts and ends the game in one go, with the same checks as StartGame + EndGame
+#[allow(clippy::too_many_arguments)]
+pub fn try_playsigned(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    host: Addr,
+    opponent: Addr,
+    wager: Option<Coin>,
+    expires: Expiration,
+    host_move: SignedMove,
+    opponent_move: SignedMove,
+) -> Result<Response, ContractError> {
+    // whatever was sent along tops up the sender's balance, as everywhere else
+    deposit_funds(deps.storage, &info.sender, &info.funds)?;
+    let checked_host = deps.api.addr_validate(host.as_ref())?;
+    let checked_opponent = deps.api.addr_validate(opponent.as_ref())?;
+
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::GameExpired {});
+    }
+
+    let nonce = SIGNED_GAME_NONCES
+        .may_load(deps.storage, (&checked_host, &checked_opponent))?
+        .unwrap_or_default();
+    // both players signed the same payload, with the commitments of the revealed moves
+    let payload = SignedMovePayload {
+        domain: SIGNED_MOVE_DOMAIN.to_string(),
+        contract: env.contract.address.clone(),
+        host: checked_host.clone(),
+        opponent: checked_opponent.clone(),
+        nonce,
+        wager: wager.clone(),
+        host_commitment: move_commitment(&host_move.game_move, &host_move.salt),
+        opponent_commitment: move_commitment(&opponent_move.game_move, &opponent_move.salt),
+        expires,
+    }
+    .to_bytes()?;
+    for (player, signed) in [
+        (&checked_host, &host_move),
+        (&checked_opponent, &opponent_move),
+    ] {
+        let key = SIGNING_KEYS
+            .may_load(deps.storage, player)?
+            .ok_or_else(|| ContractError::NoSigningKey {
+                addr: player.clone(),
+            })?;
+        if !verify_signature(deps.api, &key, &payload, &signed.signature) {
+            return Err(ContractError::InvalidSignature {
+                addr: player.clone(),
+            });
+        }
+    }
+    SIGNED_GAME_NONCES.save(
+        deps.storage,
+        (&checked_host, &checked_opponent),
+        &(nonce + 1),
+    )?;
+
+    let terms = GameTerms {
+        wager,
+        ruleset: Ruleset::Classic {},
+        best_of: 1,
+        timeout: None,
+    };
+    let res = start_game(
+        deps.branch(),
+        env.clone(),
+        checked_host.clone(),
+        checked_opponent.clone(),
+        Some(host_move.game_move),
+        terms,
+        None,
+        None,
+    )?;
+    let ended = end_game(
+        deps,
+        env,
+        checked_opponent,
+        checked_host,
+        opponent_move.game_move,
+    )?;
+    Ok(res
+        .add_submessages(ended.messages)
+        .add_attributes(ended.attributes.into_iter().filter(|a| a.key != "method"))
+        .add_attribute("method", "try_playsigned")
+        .add_attribute("nonce", nonce.to_string()))
+}
+
+// relayer pays the gas, the player only signs, the relayed message runs as if the player sent it
+pub fn try_executesigned(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    payload: Binary,
+    signature: Binary,
+    pubkey: Binary,
+) -> Res<Response> {
+    // relayer's funds couldn't be credited to anyone sensible
+    nonpayable(&info).map_err(ContractError::from)?;
+
+    // player addresses have the same prefix as the contract's own
+    let key = SigningKey {
+        key_type: KeyType::Secp256k1,
+        pubkey,
+    };
+    let player = bech32::decode(env.contract.address.as_str())
+        .ok()
+        .and_then(|(prefix, _, _)| derive_address(&prefix, &key.pubkey))
+        .ok_or(ContractError::InvalidSigningKey {})?;
+    let player = deps.api.addr_validate(&player)?;
+    if !verify_signature(deps.api, &key, &payload, &signature) {
+        return Err(ContractError::InvalidSignature { addr: player }.into());
+    }
+
+    let relayed: RelayedMsg = from_binary(&payload)?;
+    if relayed.domain != RELAYED_MSG_DOMAIN
+        || relayed.chain_id != env.block.chain_id
+        || relayed.contract != env.contract.address
+        || matches!(relayed.msg, ExecuteMsg::ExecuteSigned { .. })
+    {
+        return Err(ContractError::InvalidRelayedMsg {}.into());
+    }
+    let nonce = RELAY_NONCES
+        .may_load(deps.storage, &player)?
+        .unwrap_or_default();
+    if relayed.nonce != nonce {
+        return Err(ContractError::WrongNonce { expected: nonce }.into());
+    }
+    RELAY_NONCES.save(deps.storage, &player, &(nonce + 1))?;
+
+    let info = MessageInfo {
+        sender: player.clone(),
+        funds: vec![],
+    };
+    let res = execute(deps, env, info, relayed.msg)?;
+    Ok(res
+        .add_attribute("relayed_for", player)
+        .add_attribute("nonce", nonce.to_string()))
+}
+
+pub fn try_proposegame(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    opponent: Addr,
+    terms: GameTerms,
+) -> Result<Response, ContractError> {
+    deposit_funds(deps.storage, &info.sender, &info.funds)?;
+    let res = start_game(deps, env, info.sender, opponent, None, terms, None, None)?;
+    Ok(res.add_attribute("method", "try_proposegame"))
+}
+
+// shared by plain StartGame, ProposeGame and ProposeGame received along with an NFT
+// host_move is only known upfront in plain StartGame
+#[allow(clippy::too_many_arguments)]
+fn start_game(
+    deps: DepsMut,
+    env: Env,
+    host: Addr,
+    opponent: Addr,
+    host_move: Option<GameMove>,
+    terms: GameTerms,
+    host_nft: Option<EscrowedNft>,
+    opponent_collection: Option<Addr>,
+) -> Result<Response, ContractError> {
+    // check Addr
+    let checked_opponent: Addr = deps.api.addr_validate(opponent.as_ref())?;
+
+    // check that not trying to start game against oneself
+    if checked_opponent == host {
+        return Err(ContractError::GameAgainstYourself {});
+    }
+
+    // check if opponent isn't blacklisted
+    assert_not_blacklisted(deps.storage, &env, &checked_opponent)?;
+
+    // check if this message sender (i.e. host) isn't blacklisted
+    assert_not_blacklisted(deps.storage, &env, &host)?;
+
+    // in allowlist mode, host has to be on it
+    assert_allowlisted(deps.storage, &host)?;
+
+    // opponent doesn't want challenges from this host
+    if BLOCKS.has(deps.storage, (&checked_opponent, &host)) {
+        return Err(ContractError::BlockedByPlayer {
+            addr: checked_opponent,
+        });
+    }
+
+    let opponent_collection = opponent_collection
+        .map(|collection| deps.api.addr_validate(collection.as_ref()))
+        .transpose()?;
+
+    let config = CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    validate_terms(&terms, &config)?;
+    if let Some(max) = config.max_open_games {
+        let open_games = games()
+            .idx
+            .host
+            .prefix(host.clone())
+            .keys(deps.storage, None, None, Order::Ascending)
+            .take(max as usize)
+            .count();
+        if open_games >= max as usize {
+            return Err(ContractError::TooManyOpenGames { max });
+        }
+    }
+
+    // host's part of the wager and the bond are escrowed in the game right away
+    if let Some(wager) = &terms.wager {
+        debit_balance(deps.storage, &host, wager)?;
+    }
+    if let Some(bond) = &config.game_bond {
+        debit_balance(deps.storage, &host, bond)?;
+    }
+
+    // create the Game struct from the submitted data
+    let timeout = terms.timeout.unwrap_or(DEFAULT_GAME_TIMEOUT);
+    let game = Game {
+        host,
+        opponent: checked_opponent,
+        status: GameStatus::Proposed,
+        host_move,
+        host_commitment: None,
+        opponent_move: None, // first move by host = no move by opponent
+        result: None,        // result only after opponent move
+        wager: terms.wager,
+        host_nft,
+        opponent_collection,
+        opponent_nft: None, // opponent stakes only when answering
+        opponent_nft_accepted: false,
+        bond: config.game_bond,
+        host_hand: terms.ruleset.starting_hand(),
+        opponent_hand: terms.ruleset.starting_hand(),
+        minus_one: terms.ruleset.minus_one(),
+        beacon: None,
+        ruleset: terms.ruleset,
+        best_of: terms.best_of,
+        score: Score::default(),
+        timeout,
+        counter_offers: vec![],
+        expires: timeout.after(&env.block),
+    };
+
+    let hook_msgs = game_hooks(
+        deps.storage,
+        RpsHookMsg::GameStarted {
+            host: game.host.clone(),
+            opponent: game.opponent.clone(),
+            wager: game.wager.clone(),
+        },
+    )?;
+
+    // save the new struct and create response accordingly
+    save_game(deps, game)?;
+    Ok(Response::new().add_submessages(hook_msgs))
+}
+
+pub fn try_acceptchallenge(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    host: Addr,
+) -> Result<Response, ContractError> {
+    deposit_funds(deps.storage, &info.sender, &info.funds)?;
+    accept_challenge(deps, env, info.sender, host, None)
+}
+
+// shared by plain AcceptChallenge and AcceptChallenge received along with an NFT
+fn accept_challenge(
+    mut deps: DepsMut,
+    env: Env,
+    opponent: Addr,
+    host: Addr,
+    opponent_nft: Option<EscrowedNft>,
+) -> Result<Response, ContractError> {
+    assert_not_blacklisted(deps.storage, &env, &opponent)?;
+    assert_allowlisted(deps.storage, &opponent)?;
+
+    // plain StartGame games are answered with EndGame right away
+    let mut game = load_challenge(deps.as_ref(), &env, host, opponent)?;
+
+    // NFT games have to be accepted with an NFT and vice versa
+    match (&game.host_nft, &opponent_nft) {
+        (Some(_), None) => return Err(ContractError::NftStakeRequired {}),
+        (None, Some(_)) => return Err(ContractError::UnexpectedNftStake {}),
+        (Some(_), Some(nft)) => {
+            if let Some(collection) = &game.opponent_collection {
+                if collection != &nft.contract {
+                    return Err(ContractError::NftCollectionNotAllowed {
+                        collection: nft.contract.clone(),
+                    });
+                }
+            }
         }
+        (None, None) => {}
+    }
+    game.opponent_nft = opponent_nft;
+
+    // opponent matches the wager
+    if let Some(wager) = &game.wager {
+        debit_balance(deps.storage, &game.opponent, wager)?;
+    }
+    // accepting the current terms drops the opponent's own pending counter offer
+    if let Some(offer) = game.pending_counter_offer() {
+        offer.status = CounterOfferStatus::Rejected;
+    }
+    game.status = GameStatus::Accepted;
+    game.expires = game.timeout.after(&env.block);
+    update_game(&mut deps, game)?;
+
+    Ok(Response::new().add_attribute("method", "try_acceptchallenge"))
+}
+
+pub fn try_declinechallenge(
+    deps: DepsMut,
+    info: MessageInfo,
+    host: Addr,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let game = games()
+        .may_load(deps.storage, (host, info.sender))?
+        .ok_or(ContractError::GameNotFound {})?;
+    if game.status != GameStatus::Proposed {
+        return Err(ContractError::WrongGameStatus {
+            status: game.status,
+        });
+    }
+
+    cancel_game(deps, game, "try_declinechallenge")
+}
+
+pub fn try_acceptnft(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    opponent: Addr,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut game = load_nft_review(deps.as_ref(), &env, info.sender, opponent)?;
+    game.opponent_nft_accepted = true;
+    game.expires = game.timeout.after(&env.block);
+    update_game(&mut deps, game)?;
+
+    Ok(Response::new().add_attribute("method", "try_acceptnft"))
+}
+
+pub fn try_rejectnft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    opponent: Addr,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let game = load_nft_review(deps.as_ref(), &env, info.sender, opponent)?;
+    cancel_game(deps, game, "try_rejectnft")
+}
+
+// loads a game whose host still has to accept or reject the opponent's NFT
+fn load_nft_review(
+    deps: Deps,
+    env: &Env,
+    host: Addr,
+    opponent: Addr,
+) -> Result<Game, ContractError> {
+    let game = games()
+        .may_load(deps.storage, (host, opponent))?
+        .ok_or(ContractError::GameNotFound {})?;
+    if game.status != GameStatus::Accepted || !game.awaiting_nft_review() {
+        return Err(ContractError::NoNftToReview {});
+    }
+    if game.expires.is_expired(&env.block) {
+        return Err(ContractError::GameExpired {});
+    }
+    Ok(game)
+}
+
+pub fn try_counterchallenge(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    host: Addr,
+    terms: GameTerms,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut game = load_challenge(deps.as_ref(), &env, host, info.sender)?;
+    let config = CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    validate_terms(&terms, &config)?;
+    if game.pending_counter_offer().is_some() {
+        return Err(ContractError::WrongGameStatus {
+            status: game.status,
+        });
+    }
+    if game.counter_offers.len() >= MAX_COUNTER_OFFERS {
+        return Err(ContractError::TooManyCounterOffers {
+            max: MAX_COUNTER_OFFERS,
+        });
+    }
+
+    // host gets the full timeout to answer
+    game.counter_offers.push(CounterOffer {
+        terms,
+        status: CounterOfferStatus::Pending,
+    });
+    game.expires = game.timeout.after(&env.block);
+    update_game(&mut deps, game)?;
+
+    Ok(Response::new().add_attribute("method", "try_counterchallenge"))
+}
+
+pub fn try_acceptcounter(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    opponent: Addr,
+) -> Result<Response, ContractError> {
+    deposit_funds(deps.storage, &info.sender, &info.funds)?;
+    let mut game = load_challenge(deps.as_ref(), &env, info.sender, opponent)?;
+    let offer = game
+        .pending_counter_offer()
+        .ok_or(ContractError::NoCounterOffer {})?;
+    offer.status = CounterOfferStatus::Accepted;
+    let terms = offer.terms.clone();
+
+    // host's escrow follows the new wager
+    if let Some(wager) = &game.wager {
+        credit_balance(deps.storage, &game.host, wager)?;
+    }
+    if let Some(wager) = &terms.wager {
+        debit_balance(deps.storage, &game.host, wager)?;
+    }
+    game.wager = terms.wager;
+    game.host_hand = terms.ruleset.starting_hand();
+    game.opponent_hand = terms.ruleset.starting_hand();
+    game.minus_one = terms.ruleset.minus_one();
+    game.ruleset = terms.ruleset;
+    game.best_of = terms.best_of;
+    game.timeout = terms.timeout.unwrap_or(DEFAULT_GAME_TIMEOUT);
+    game.expires = game.timeout.after(&env.block);
+    update_game(&mut deps, game)?;
+
+    Ok(Response::new().add_attribute("method", "try_acceptcounter"))
+}
+
+pub fn try_rejectcounter(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    opponent: Addr,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut game = load_challenge(deps.as_ref(), &env, info.sender, opponent)?;
+    let offer = game
+        .pending_counter_offer()
+        .ok_or(ContractError::NoCounterOffer {})?;
+    offer.status = CounterOfferStatus::Rejected;
+    game.expires = game.timeout.after(&env.block);
+    update_game(&mut deps, game)?;
+
+    Ok(Response::new().add_attribute("method", "try_rejectcounter"))
+}
+
+// loads a challenge that is still being negotiated
+fn load_challenge(
+    deps: Deps,
+    env: &Env,
+    host: Addr,
+    opponent: Addr,
+) -> Result<Game, ContractError> {
+    let game = games()
+        .may_load(deps.storage, (host, opponent))?
+        .ok_or(ContractError::GameNotFound {})?;
+    // plain StartGame games have the host move locked in already, no negotiation there
+    if game.status != GameStatus::Proposed || game.host_move.is_some() {
+        return Err(ContractError::WrongGameStatus {
+            status: game.status,
+        });
+    }
+    if game.expires.is_expired(&env.block) {
+        return Err(ContractError::GameExpired {});
+    }
+    Ok(game)
+}
+
+pub fn try_commitmove(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    opponent: Addr,
+    commitment: Binary,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut game = games()
+        .may_load(deps.storage, (info.sender, opponent))?
+        .ok_or(ContractError::GameNotFound {})?;
+    if game.status != GameStatus::Accepted {
+        return Err(ContractError::WrongGameStatus {
+            status: game.status,
+        });
+    }
+    if game.minus_one.is_some() {
+        return Err(ContractError::RulesetMismatch {});
+    }
+    if game.host_commitment.is_some() {
+        return Err(ContractError::MoveAlreadyCommitted {});
+    }
+    if game.awaiting_nft_review() {
+        return Err(ContractError::NftNotAccepted {});
+    }
+    if game.expires.is_expired(&env.block) {
+        return Err(ContractError::GameExpired {});
+    }
+
+    // opponent gets the full timeout to answer
+    game.host_commitment = Some(commitment);
+    game.expires = game.timeout.after(&env.block);
+    update_game(&mut deps, game)?;
+
+    Ok(Response::new().add_attribute("method", "try_commitmove"))
+}
+
+pub fn try_revealmove(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    opponent: Addr,
+    host_move: GameMove,
+    salt: String,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut game = games()
+        .may_load(deps.storage, (info.sender, opponent))?
+        .ok_or(ContractError::GameNotFound {})?;
+    if game.status != GameStatus::AwaitingReveal {
+        return Err(ContractError::WrongGameStatus {
+            status: game.status,
+        });
+    }
+    if game.expires.is_expired(&env.block) {
+        return Err(ContractError::GameExpired {});
+    }
+
+    match &game.host_commitment {
+        Some(commitment) if verify_move(commitment, &host_move, &salt) => {}
+        _ => return Err(ContractError::InvalidCommitment {}),
+    }
+    game.host_move = Some(host_move);
+
+    play_round(deps, &env, game, "try_revealmove")
+}
+
+// host commits first, the bot accepts and its answer is drawn from a beacon published
+// after the commitment, so neither side knows the other's move when playing their own
+pub fn try_commitchallenge(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    opponent: Addr,
+    commitment: Binary,
+    wager: Option<Coin>,
+) -> Result<Response, ContractError> {
+    let beacon_config = CONFIG
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .beacon
+        .ok_or(ContractError::BeaconNotConfigured {})?;
+    if !BOTS.has(deps.storage, &opponent)
+        && !take_standing_challenge(deps.storage, &env, &opponent, &wager)?
+    {
+        return Err(ContractError::NoAutoAnswer { addr: opponent });
+    }
+    deposit_funds(deps.storage, &info.sender, &info.funds)?;
+    let terms = GameTerms {
+        wager,
+        ruleset: Ruleset::Classic {},
+        best_of: 1,
+        timeout: None,
+    };
+    let res = start_game(
+        deps.branch(),
+        env.clone(),
+        info.sender.clone(),
+        opponent.clone(),
+        None,
+        terms,
+        None,
+        None,
+    )?;
+
+    // a bot or the player's strategy matches the wager from the opponent's balance
+    let mut game = games().load(deps.storage, (info.sender, opponent))?;
+    if let Some(wager) = &game.wager {
+        debit_balance(deps.storage, &game.opponent, wager)?;
+    }
+    let beacon = upcoming_beacon(&beacon_config, &env);
+    let round = beacon.round;
+    game.status = GameStatus::Accepted;
+    game.host_commitment = Some(commitment);
+    game.beacon = Some(beacon);
+    update_game(&mut deps, game)?;
+
+    Ok(res
+        .add_attribute("method", "try_commitchallenge")
+        .add_attribute("round", round.to_string()))
+}
+
+// the bot gets the beacon's randomness along with the challenge, its move comes back in reply
+// a standing strategy answers right away
+#[allow(clippy::too_many_arguments)]
+pub fn try_submitchallengebeacon(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    host: Addr,
+    opponent: Addr,
+    round: u64,
+    previous_signature: Binary,
+    signature: Binary,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut game = games()
+        .may_load(deps.storage, (host, opponent))?
+        .ok_or(ContractError::GameNotFound {})?;
+    let beacon = match (&game.status, game.beacon.take()) {
+        (GameStatus::Accepted, Some(beacon)) => beacon,
+        _ => {
+            return Err(ContractError::WrongGameStatus {
+                status: game.status,
+            })
+        }
+    };
+    if game.expires.is_expired(&env.block) {
+        return Err(ContractError::GameExpired {});
+    }
+    let randomness = verify_beacon_round(&beacon, round, &previous_signature, &signature)?;
+
+    if !BOTS.has(deps.storage, &game.opponent) {
+        let answer = standing_answer(deps.storage, &game.opponent, &game.host, &randomness)?;
+        let (host, opponent) = (game.host.clone(), game.opponent.clone());
+        update_game(&mut deps, game.clone())?;
+        return match answer {
+            Some(answer) => {
+                let ended = end_game(deps, env, opponent, host, answer)?;
+                Ok(Response::new()
+                    .add_attribute("method", "try_submitchallengebeacon")
+                    .add_submessages(ended.messages)
+                    .add_attributes(ended.attributes.into_iter().filter(|a| a.key != "method"))
+                    .add_attribute("standing_strategy", "true"))
+            }
+            // strategy was cleared or the bot removed in the meantime, nobody is going to answer
+            None => cancel_game(deps, game, "try_submitchallengebeacon"),
+        };
+    }
+    let id = next_reply_id(deps.storage)?;
+    PENDING_BOT_GAMES.save(
+        deps.storage,
+        id,
+        &(game.host.clone(), game.opponent.clone()),
+    )?;
+    let challenge = BotChallengeMsg {
+        host: game.host.clone(),
+        wager: game.wager.clone(),
+        randomness: Binary::from(randomness.to_vec()),
+    };
+    let msg = challenge.into_cosmos_msg(game.opponent.clone())?;
+    update_game(&mut deps, game)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_submitchallengebeacon")
+        .add_submessage(SubMsg::reply_always(msg, id)))
+}
+
+pub fn try_commitminusone(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    host: Addr,
+    opponent: Addr,
+    commitment: Binary,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let phase = MinusOnePhase::CommitMoves;
+    let (game, mut minus_one, is_host) =
+        load_minus_one(deps.as_ref(), &env, host, opponent, &info.sender, phase)?;
+    let hand = minus_one.hand_mut(is_host);
+    if hand.commitment.is_some() {
+        return Err(ContractError::MoveAlreadyCommitted {});
+    }
+    hand.commitment = Some(commitment);
+
+    minus_one_step(deps, &env, game, minus_one, "try_commitminusone")
+}
+
+pub fn try_revealminusone(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    host: Addr,
+    opponent: Addr,
+    moves: (GameMove, GameMove),
+    salt: String,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let phase = MinusOnePhase::RevealMoves;
+    let (game, mut minus_one, is_host) =
+        load_minus_one(deps.as_ref(), &env, host, opponent, &info.sender, phase)?;
+    let hand = minus_one.hand_mut(is_host);
+    match hand.commitment.take() {
+        Some(commitment) if verify_moves(&commitment, &moves, &salt) => {}
+        _ => return Err(ContractError::InvalidCommitment {}),
+    }
+    hand.moves = Some(moves);
+
+    minus_one_step(deps, &env, game, minus_one, "try_revealminusone")
+}
+
+pub fn try_commitwithdrawal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    host: Addr,
+    opponent: Addr,
+    commitment: Binary,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let phase = MinusOnePhase::CommitWithdrawal;
+    let (game, mut minus_one, is_host) =
+        load_minus_one(deps.as_ref(), &env, host, opponent, &info.sender, phase)?;
+    let hand = minus_one.hand_mut(is_host);
+    if hand.commitment.is_some() {
+        return Err(ContractError::MoveAlreadyCommitted {});
+    }
+    hand.commitment = Some(commitment);
+
+    minus_one_step(deps, &env, game, minus_one, "try_commitwithdrawal")
+}
+
+pub fn try_revealwithdrawal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    host: Addr,
+    opponent: Addr,
+    withdrawn: GameMove,
+    salt: String,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let phase = MinusOnePhase::RevealWithdrawal;
+    let (game, mut minus_one, is_host) =
+        load_minus_one(deps.as_ref(), &env, host, opponent, &info.sender, phase)?;
+    let hand = minus_one.hand_mut(is_host);
+    match hand.commitment.take() {
+        Some(commitment) if verify_move(&commitment, &withdrawn, &salt) => {}
+        _ => return Err(ContractError::InvalidCommitment {}),
+    }
+    match &hand.moves {
+        Some((first, second)) if first == &withdrawn || second == &withdrawn => {}
+        _ => return Err(ContractError::WithdrawnMoveNotShown {}),
+    }
+    hand.withdrawn = Some(withdrawn);
+
+    minus_one_step(deps, &env, game, minus_one, "try_revealwithdrawal")
+}
+
+// loads an accepted minus one game in the given phase, along with which side the player is on
+fn load_minus_one(
+    deps: Deps,
+    env: &Env,
+    host: Addr,
+    opponent: Addr,
+    player: &Addr,
+    phase: MinusOnePhase,
+) -> Result<(Game, MinusOne, bool), ContractError> {
+    let mut game = games()
+        .may_load(deps.storage, (host, opponent))?
+        .ok_or(ContractError::GameNotFound {})?;
+    let is_host = if player == &game.host {
+        true
+    } else if player == &game.opponent {
+        false
+    } else {
+        return Err(ContractError::Unauthorized {});
+    };
+    if game.status != GameStatus::Accepted {
+        return Err(ContractError::WrongGameStatus {
+            status: game.status,
+        });
+    }
+    if game.awaiting_nft_review() {
+        return Err(ContractError::NftNotAccepted {});
+    }
+    if game.expires.is_expired(&env.block) {
+        return Err(ContractError::GameExpired {});
+    }
+    let minus_one = game
+        .minus_one
+        .take()
+        .ok_or(ContractError::RulesetMismatch {})?;
+    if minus_one.phase != phase {
+        return Err(ContractError::WrongMinusOnePhase {
+            phase: minus_one.phase,
+        });
+    }
+
+    Ok((game, minus_one, is_host))
+}
+
+// once both players are done with a phase the next one starts (with the full timeout),
+// after the withdrawals the remaining moves are played as a round
+fn minus_one_step(
+    mut deps: DepsMut,
+    env: &Env,
+    mut game: Game,
+    mut minus_one: MinusOne,
+    method: &str,
+) -> Result<Response, ContractError> {
+    if !(minus_one.acted(true) && minus_one.acted(false)) {
+        game.minus_one = Some(minus_one);
+        update_game(&mut deps, game)?;
+        return Ok(Response::new().add_attribute("method", method));
+    }
+
+    minus_one.phase = match minus_one.phase {
+        MinusOnePhase::CommitMoves => MinusOnePhase::RevealMoves,
+        MinusOnePhase::RevealMoves => MinusOnePhase::CommitWithdrawal,
+        MinusOnePhase::CommitWithdrawal => MinusOnePhase::RevealWithdrawal,
+        MinusOnePhase::RevealWithdrawal => {
+            game.host_move = kept_move(&minus_one.host);
+            game.opponent_move = kept_move(&minus_one.opponent);
+            // next round of a series starts over
+            game.minus_one = Some(MinusOne::default());
+            return play_round(deps, env, game, method);
+        }
+    };
+    let phase = minus_one.phase.as_str().to_string();
+    game.minus_one = Some(minus_one);
+    game.expires = game.timeout.after(&env.block);
+    update_game(&mut deps, game)?;
+
+    Ok(Response::new()
+        .add_attribute("method", method)
+        .add_attribute("phase", phase))
+}
+
+// the move a player didn't withdraw
+fn kept_move(hand: &MinusOneHand) -> Option<GameMove> {
+    match (&hand.moves, &hand.withdrawn) {
+        (Some((first, second)), Some(withdrawn)) if first == withdrawn => Some(second.clone()),
+        (Some((first, _)), Some(_)) => Some(first.clone()),
+        _ => None,
+    }
+}
+
+fn validate_terms(terms: &GameTerms, config: &Config) -> Result<(), ContractError> {
+    checked_timeout(config, terms.timeout)?;
+    if terms.best_of % 2 != 1 || terms.best_of > MAX_BEST_OF {
+        return Err(ContractError::InvalidBestOf { max: MAX_BEST_OF });
+    }
+    // a hand too small for the series would leave it undecided
+    if let Some(hand) = terms.ruleset.starting_hand() {
+        if hand.size() < terms.best_of as u32 {
+            return Err(ContractError::InvalidHand {});
+        }
+    }
+    match &terms.wager {
+        Some(wager) if wager.amount.is_zero() => Err(ContractError::InvalidWager {}),
+        _ => Ok(()),
+    }
+}
+
+// timeout of a game or phase, DEFAULT_GAME_TIMEOUT if not given
+// has to be within the admin's limits, or the other side could be forfeited before it could act
+fn checked_timeout(config: &Config, timeout: Option<Duration>) -> Result<Duration, ContractError> {
+    let timeout = timeout.unwrap_or(DEFAULT_GAME_TIMEOUT);
+    if !config.timeout_limits().allows(&timeout) {
+        return Err(ContractError::InvalidTimeout {});
+    }
+    Ok(timeout)
+}
+
+// helper function to create new game, wrapping IndexedMap update syntax
+pub fn save_game(deps: DepsMut, game: Game) -> Result<Game, ContractError> {
+    games().update(
+        deps.storage,
+        generate_key_for_game(&game), // call helper function to get the key
+        |old| match old {
+            Some(_) => Err(ContractError::DuplicateGame {}), // if the game already exists, update() will fail here. prevents overwriting.
+            None => Ok(game),                                // if it doesn't exist, it's created
+        },
+    )
+}
+
+// helper function to update existing game, wrapping IndexedMap update sytax
+// unlike save_game(), this one will fail if the game doesn't exist yet
+pub fn update_game(deps: &mut DepsMut, game: Game) -> Result<Game, ContractError> {
+    games().update(
+        deps.storage,
+        generate_key_for_game(&game),
+        |old| match old {
+            Some(_) => Ok(game),                         // if game exists, update it
+            None => Err(ContractError::GameNotFound {}), // if game doesn't exist yet, fail
+        },
+    )
+}
+
+// helper to wrap IndexedMap delete syntax
+pub fn delete_game(deps: &mut DepsMut, game: Game) -> Result<(), StdError> {
+    games().remove(deps.storage, generate_key_for_game(&game))
+}
+
+// helper that will create (host, opponent) tuple key from Game struct
+pub fn generate_key_for_game(game: &Game) -> (Addr, Addr) {
+    (game.host.clone(), game.opponent.clone())
+}
+
+pub fn try_endgame(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    host: Addr,
+    opponent_move: GameMove,
+) -> Result<Response, ContractError> {
+    // whatever was sent along tops up the balance the wager is taken from
+    deposit_funds(deps.storage, &info.sender, &info.funds)?;
+    end_game(deps, env, info.sender, host, opponent_move)
+}
+
+// shared by EndGame and the automatic answers of bots and standing strategies
+fn end_game(
+    mut deps: DepsMut,
+    env: Env,
+    opponent: Addr,
+    host: Addr,
+    opponent_move: GameMove,
+) -> Result<Response, ContractError> {
+    // check Addr
+    let checked_host: Addr = deps.api.addr_validate(host.as_ref())?;
+    // might not be necessary to check host against blacklist, coz he couldn't have started the game if blacklisted
+    // however, he could have gotten blacklisted after starting the game...
+
+    // check if host is not blacklisted (got blacklisted after the game was started)
+    assert_not_blacklisted(deps.storage, &env, &checked_host)?;
+
+    // check if this message sender isn't blacklisted
+    assert_not_blacklisted(deps.storage, &env, &opponent)?;
+
+    // in allowlist mode, opponent has to be on it
+    assert_allowlisted(deps.storage, &opponent)?;
+
+    // lookup game by host, opponent
+    let game_res = games().may_load(deps.storage, (checked_host, opponent));
+    let mut game = match game_res {
+        Ok(Some(game)) => game,
+        Ok(None) => return Err(ContractError::GameNotFound {}),
+        Err(_e) => return Err(ContractError::GameNotFound {}),
+    };
+
+    // too late to answer, the game can only be expired now
+    if game.expires.is_expired(&env.block) {
+        return Err(ContractError::GameExpired {});
+    }
+
+    match game.status {
+        // plain game, opponent joins and plays at once
+        GameStatus::Proposed if game.host_move.is_some() => {}
+        GameStatus::Proposed => return Err(ContractError::ChallengeNotAccepted {}),
+        // challenge, opponent plays once the host committed, host reveals afterwards
+        GameStatus::Accepted => {
+            if game.host_commitment.is_none() {
+                return Err(ContractError::MoveNotCommitted {});
+            }
+            // used up once the host reveals, but rejected right away
+            if let Some(hand) = &game.opponent_hand {
+                if hand.remaining(&opponent_move) == 0 {
+                    return Err(ContractError::MoveExhausted {
+                        game_move: opponent_move,
+                    });
+                }
+            }
+            game.opponent_move = Some(opponent_move);
+            game.status = GameStatus::AwaitingReveal;
+            game.expires = game.timeout.after(&env.block);
+            update_game(&mut deps, game)?;
+
+            return Ok(Response::new()
+                .add_attribute("method", "try_endgame")
+                .add_attribute("status", "awaiting_reveal"));
+        }
+        _ => {
+            return Err(ContractError::WrongGameStatus {
+                status: game.status,
+            })
+        }
+    }
+
+    // opponent matches the wager
+    if let Some(wager) = &game.wager {
+        debit_balance(deps.storage, &game.opponent, wager)?;
+    }
+
+    game.opponent_move = Some(opponent_move);
+    play_round(deps, &env, game, "try_endgame")
+}
+
+// find out round result
+fn round_result(
+    host_move: &GameMove,
+    opponent_move: &GameMove,
+) -> Result<GameResult, ContractError> {
+    match (host_move, opponent_move) {
+        // host starts with Rock
+        (GameMove::Rock {}, GameMove::Paper {}) => Ok(GameResult::OpponentWins {}),
+        (GameMove::Rock {}, GameMove::Scissors {}) => Ok(GameResult::HostWins {}),
+
+        // host starts with Paper
+        (GameMove::Paper {}, GameMove::Rock {}) => Ok(GameResult::HostWins {}),
+        (GameMove::Paper {}, GameMove::Scissors {}) => Ok(GameResult::OpponentWins {}),
+
+        // host starts with Scissors
+        (GameMove::Scissors {}, GameMove::Rock {}) => Ok(GameResult::OpponentWins {}),
+        (GameMove::Scissors {}, GameMove::Paper {}) => Ok(GameResult::HostWins {}),
+
+        // same moves = tie
+        (move1, move2) if (move1 == move2) => Ok(GameResult::Tie {}),
+        (_, _) => Err(ContractError::GameResultNotFound {}),
+    }
+}
+
+// get a human friendly string message
+fn result_string(result: &GameResult) -> &'static str {
+    match result {
+        GameResult::Tie {} => "Tie",
+        GameResult::HostWins {} => "Host won",
+        GameResult::OpponentWins {} => "Opponent won",
+    }
+}
+
+// evaluates the round once both moves are known, then either finishes the game
+// or starts the next round of the series
+fn play_round(
+    mut deps: DepsMut,
+    env: &Env,
+    mut game: Game,
+    method: &str,
+) -> Result<Response, ContractError> {
+    let result = match (&game.host_move, &game.opponent_move) {
+        (Some(host_move), Some(opponent_move)) => round_result(host_move, opponent_move)?,
+        _ => return Err(ContractError::GameResultNotFound {}),
+    };
+    // in a limited hand game both moves are used up
+    for (hand, game_move) in [
+        (&mut game.host_hand, &game.host_move),
+        (&mut game.opponent_hand, &game.opponent_move),
+    ] {
+        if let (Some(hand), Some(game_move)) = (hand, game_move) {
+            if !hand.play(game_move) {
+                return Err(ContractError::MoveExhausted {
+                    game_move: game_move.clone(),
+                });
+            }
+        }
+    }
+    match result {
+        GameResult::HostWins {} => game.score.host += 1,
+        GameResult::OpponentWins {} => game.score.opponent += 1,
+        GameResult::Tie {} => {}
+    }
+
+    // single games can end in a tie, tied rounds of a series are replayed
+    let to_win = game.best_of / 2 + 1;
+    let final_result = if game.score.host >= to_win {
+        Some(GameResult::HostWins {})
+    } else if game.score.opponent >= to_win {
+        Some(GameResult::OpponentWins {})
+    } else if game.best_of == 1 {
+        Some(GameResult::Tie {})
+    } else if matches!(&game.host_hand, Some(hand) if hand.size() == 0) {
+        // out of moves (both hands shrink together), whoever leads wins
+        Some(match game.score.host.cmp(&game.score.opponent) {
+            std::cmp::Ordering::Greater => GameResult::HostWins {},
+            std::cmp::Ordering::Less => GameResult::OpponentWins {},
+            std::cmp::Ordering::Equal => GameResult::Tie {},
+        })
+    } else {
+        None
+    };
+    if let Some(final_result) = final_result {
+        return finish_game(deps, game, final_result, method);
+    }
+
+    // next round, host commits again
+    game.host_move = None;
+    game.host_commitment = None;
+    game.opponent_move = None;
+    game.status = GameStatus::Accepted;
+    game.expires = game.timeout.after(&env.block);
+    let score = format!("{}:{}", game.score.host, game.score.opponent);
+    update_game(&mut deps, game)?;
+
+    Ok(Response::new()
+        .add_attribute("method", method)
+        .add_attribute("round_result", result_string(&result))
+        .add_attribute("score", score))
+}
+
+// pays out the stakes, settles bets and removes the game
+fn finish_game(
+    mut deps: DepsMut,
+    mut game: Game,
+    result: GameResult,
+    method: &str,
+) -> Result<Response, ContractError> {
+    // update map accordingly
+    game.status = GameStatus::Finished;
+    game.result = Some(result.clone());
+
+    // ## actually, if I understand the docs here https://academy.terra.money/courses/take/cosmwasm-smart-contracts-i/assignments/27056622-building-out-the-rps-game
+    // ## the final state of the game is not saved in cotract
+    // ## it's just verifiable on chain (by including it in response?)
+    let updated_game = update_game(&mut deps, game)?;
+
+    // winner takes both NFTs and the whole wager (minus house fee), tie returns each stake to its owner
+    credit_wager(deps.storage, &updated_game, &result)?;
+    refund_bond(deps.storage, &updated_game)?;
+    let mut payouts = nft_payouts(&updated_game, &result);
+    // game is over, so the spectator pool is closed and can be paid out
+    payouts.append(&mut settle_bets(
+        deps.storage,
+        &updated_game,
+        Some(&result),
+    )?);
+    let payout_msgs = dispatch_payouts(deps.storage, payouts)?;
+    let hook_msgs = game_hooks(
+        deps.storage,
+        RpsHookMsg::GameEnded {
+            host: updated_game.host.clone(),
+            opponent: updated_game.opponent.clone(),
+            result: result.clone(),
+        },
+    )?;
+
+    // delete the game from the IndexedMap and create a response with game_result
+    match delete_game(&mut deps, updated_game) {
+        Ok(_) => Ok(Response::new()
+            .add_attribute("method", method)
+            .add_attribute("game_result", result_string(&result))
+            .add_submessages(payout_msgs)
+            .add_submessages(hook_msgs)),
+        Err(_) => Err(ContractError::CannotFinishGame {}),
+    }
+}
+
+// transfers of the escrowed NFTs according to game result
+// (tie = everything goes back to the owners)
+fn nft_payouts(game: &Game, result: &GameResult) -> Vec<Payout> {
+    let (host_nft_to, opponent_nft_to) = match result {
+        GameResult::HostWins {} => (&game.host, &game.host),
+        GameResult::OpponentWins {} => (&game.opponent, &game.opponent),
+        GameResult::Tie {} => (&game.host, &game.opponent),
+    };
+
+    let mut payouts = vec![];
+    if let Some(nft) = &game.host_nft {
+        payouts.push(Payout::Nft {
+            recipient: host_nft_to.clone(),
+            nft: nft.clone(),
+        });
+    }
+    if let Some(nft) = &game.opponent_nft {
+        payouts.push(Payout::Nft {
+            recipient: opponent_nft_to.clone(),
+            nft: nft.clone(),
+        });
+    }
+    payouts
+}
+
+// sends each payout as a submessage, remembering it under the reply id,
+// so that reply() can move it to claims if the transfer fails
+pub fn dispatch_payouts(storage: &mut dyn Storage, payouts: Vec<Payout>) -> StdResult<Vec<SubMsg>> {
+    payouts
+        .into_iter()
+        .map(|payout| {
+            let id = next_reply_id(storage)?;
+            let msg = payout_msg(&payout)?;
+            PENDING_PAYOUTS.save(storage, id, &payout)?;
+            Ok(SubMsg::reply_always(msg, id))
+        })
+        .collect()
+}
+
+// payouts and bot challenges share one sequence, so their reply ids never collide
+fn next_reply_id(storage: &mut dyn Storage) -> StdResult<u64> {
+    let id = PAYOUT_SEQ.may_load(storage)?.unwrap_or_default() + 1;
+    PAYOUT_SEQ.save(storage, &id)?;
+    Ok(id)
+}
+
+fn payout_msg(payout: &Payout) -> StdResult<CosmosMsg> {
+    match payout {
+        Payout::Native { recipient, amount } => Ok(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![amount.clone()],
+        }
+        .into()),
+        Payout::Nft { recipient, nft } => transfer_nft_msg(nft, recipient),
+    }
+}
+
+// credits the escrowed wager to the players' balances according to game result
+// (opponent only gets something back if they already matched the wager)
+// house fee is only taken from won games, ties are refunded in full
+fn credit_wager(storage: &mut dyn Storage, game: &Game, result: &GameResult) -> StdResult<()> {
+    let wager = match &game.wager {
+        Some(wager) => wager,
+        None => return Ok(()),
+    };
+    let mut pot = Coin {
+        denom: wager.denom.clone(),
+        amount: wager.amount.checked_mul(Uint128::new(2))?,
+    };
+    if result != &(GameResult::Tie {}) {
+        let config = CONFIG.may_load(storage)?.unwrap_or_default();
+        let fee = pot.amount.multiply_ratio(config.fee_bps, 10_000u128);
+        if !fee.is_zero() {
+            TREASURY.update(storage, &pot.denom, |collected| -> StdResult<_> {
+                Ok(collected.unwrap_or_default() + fee)
+            })?;
+            pot.amount -= fee;
+        }
+    }
+
+    match result {
+        GameResult::HostWins {} => credit_balance(storage, &game.host, &pot),
+        GameResult::OpponentWins {} => credit_balance(storage, &game.opponent, &pot),
+        GameResult::Tie {} => {
+            credit_balance(storage, &game.host, wager)?;
+            if game.opponent_joined() {
+                credit_balance(storage, &game.opponent, wager)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn refund_bond(storage: &mut dyn Storage, game: &Game) -> StdResult<()> {
+    match &game.bond {
+        Some(bond) => credit_balance(storage, &game.host, bond),
+        None => Ok(()),
+    }
+}
+
+pub fn try_expiregame(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    host: Addr,
+    opponent: Addr,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut game = games()
+        .may_load(deps.storage, (host, opponent))?
+        .ok_or(ContractError::GameNotFound {})?;
+
+    if !game.expires.is_expired(&env.block) {
+        return Err(ContractError::GameNotExpired {});
+    }
+
+    // host didn't reveal in time, so they forfeit the whole game and the bond
+    // the same goes for a CommitChallenge whose beacon wasn't submitted, the host could hold
+    // it back once the beacon shows that the bot's answer beats their move
+    // in an accepted game, whoever didn't act in time forfeits (a host loses the bond as well):
+    // the host before committing, the opponent once the host committed.
+    // only an NFT the host never reviewed is refunded, nothing was played yet
+    let forfeit = match game.status {
+        GameStatus::AwaitingReveal => Some(GameResult::OpponentWins {}),
+        GameStatus::Accepted if game.awaiting_nft_review() => None,
+        GameStatus::Accepted if game.beacon.is_some() => Some(GameResult::OpponentWins {}),
+        GameStatus::Accepted if game.minus_one.is_some() => {
+            game.minus_one.as_ref().and_then(MinusOne::forfeit)
+        }
+        GameStatus::Accepted if game.host_commitment.is_none() => Some(GameResult::OpponentWins {}),
+        GameStatus::Accepted => Some(GameResult::HostWins {}),
+        _ => None,
+    };
+    if let Some(result) = forfeit {
+        if result == (GameResult::OpponentWins {}) {
+            if let Some(bond) = game.bond.take() {
+                TREASURY.update(deps.storage, &bond.denom, |collected| -> StdResult<_> {
+                    Ok(collected.unwrap_or_default() + bond.amount)
+                })?;
+            }
+        }
+        return finish_game(deps, game, result, "try_expiregame");
+    }
+
+    cancel_game(deps, game, "try_expiregame")
+}
+
+pub fn try_cancelgame(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    host: Addr,
+    opponent: Addr,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    assert_role(deps.as_ref(), &info.sender, Role::Moderator)?;
+
+    let game = games()
+        .may_load(deps.storage, (host, opponent))?
+        .ok_or(ContractError::GameNotFound {})?;
+    let params = vec![attr("host", &game.host), attr("opponent", &game.opponent)];
+    audit(deps.storage, &env, &info.sender, "cancel_game", params)?;
+
+    cancel_game(deps, game, "try_cancelgame")
+}
+
+// removes the game without a result, shared by expiry and moderation
+fn cancel_game(mut deps: DepsMut, game: Game, method: &str) -> Result<Response, ContractError> {
+    // nobody won, so everyone gets their stake back
+    credit_wager(deps.storage, &game, &GameResult::Tie {})?;
+    refund_bond(deps.storage, &game)?;
+    let mut refunds = nft_payouts(&game, &GameResult::Tie {});
+    refunds.append(&mut settle_bets(deps.storage, &game, None)?);
+    let refund_msgs = dispatch_payouts(deps.storage, refunds)?;
+    let hook_msgs = game_hooks(
+        deps.storage,
+        RpsHookMsg::GameCancelled {
+            host: game.host.clone(),
+            opponent: game.opponent.clone(),
+        },
+    )?;
+    delete_game(&mut deps, game)?;
+
+    Ok(Response::new()
+        .add_attribute("method", method)
+        .add_submessages(refund_msgs)
+        .add_submessages(hook_msgs))
+}
+
+pub fn try_receivenft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    // the NFT contract calls us, the player is the one who sent the token
+    // only an approved collection is trusted to name the sender, any contract can call this
+    if !NFT_COLLECTIONS.has(deps.storage, &info.sender) {
+        return Err(ContractError::UnknownNftCollection { addr: info.sender });
+    }
+    let player = deps.api.addr_validate(&wrapper.sender)?;
+    let nft = EscrowedNft {
+        contract: info.sender,
+        token_id: wrapper.token_id,
+    };
+
+    match from_binary(&wrapper.msg)? {
+        NftReceiveMsg::ProposeGame {
+            opponent,
+            terms,
+            opponent_collection,
+        } => {
+            let res = start_game(
+                deps,
+                env,
+                player,
+                opponent,
+                None,
+                terms,
+                Some(nft),
+                opponent_collection,
+            )?;
+            Ok(res.add_attribute("method", "try_proposegame"))
+        }
+        NftReceiveMsg::AcceptChallenge { host } => {
+            accept_challenge(deps, env, player, host, Some(nft))
+        }
+    }
+}
+
+pub fn try_deposit(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    if info.funds.is_empty() {
+        return Err(PaymentError::NoFunds {}.into());
+    }
+    deposit_funds(deps.storage, &info.sender, &info.funds)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_deposit")
+        .add_attribute("player", info.sender))
+}
+
+pub fn try_withdraw(
+    deps: DepsMut,
+    info: MessageInfo,
+    amount: Coin,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    if amount.amount.is_zero() {
+        return Err(ContractError::InvalidWager {});
+    }
+    debit_balance(deps.storage, &info.sender, &amount)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_withdraw")
+        .add_attribute("player", info.sender.clone())
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![amount],
+        }))
+}
+
+pub fn try_claim(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let claimed_coins = CLAIMS
+        .prefix(&info.sender)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| coin(amount.u128(), denom)))
+        .collect::<StdResult<Vec<_>>>()?;
+    let claimed_nfts = NFT_CLAIMS
+        .sub_prefix(&info.sender)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, nft)| nft))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    if claimed_coins.is_empty() && claimed_nfts.is_empty() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    // plain messages this time, if the recipient still can't receive them
+    // the whole claim fails and can be retried later
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    for claimed in &claimed_coins {
+        CLAIMS.remove(deps.storage, (&info.sender, &claimed.denom));
+    }
+    if !claimed_coins.is_empty() {
+        msgs.push(
+            BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: claimed_coins,
+            }
+            .into(),
+        );
+    }
+    for nft in claimed_nfts {
+        NFT_CLAIMS.remove(deps.storage, (&info.sender, &nft.contract, &nft.token_id));
+        msgs.push(transfer_nft_msg(&nft, &info.sender)?);
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "try_claim")
+        .add_attribute("player", info.sender)
+        .add_messages(msgs))
+}
+
+// helper to add all sent coins to player's balance
+pub fn deposit_funds(storage: &mut dyn Storage, player: &Addr, funds: &[Coin]) -> StdResult<()> {
+    for fund in funds {
+        credit_balance(storage, player, fund)?;
+    }
+    Ok(())
+}
+
+// helper to add coin to player's balance, wrapping Map update syntax
+pub fn credit_balance(storage: &mut dyn Storage, player: &Addr, amount: &Coin) -> StdResult<()> {
+    BALANCES.update(
+        storage,
+        (player, &amount.denom),
+        |balance| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount.amount) },
+    )?;
+    Ok(())
+}
+
+// helper to take coin from player's balance, fails if there's not enough
+pub fn debit_balance(
+    storage: &mut dyn Storage,
+    player: &Addr,
+    amount: &Coin,
+) -> Result<(), ContractError> {
+    let available = BALANCES
+        .may_load(storage, (player, &amount.denom))?
+        .unwrap_or_default();
+    if available < amount.amount {
+        return Err(ContractError::InsufficientBalance {
+            denom: amount.denom.clone(),
+            available,
+        });
+    }
+
+    let remaining = available - amount.amount;
+    if remaining.is_zero() {
+        BALANCES.remove(storage, (player, &amount.denom));
+    } else {
+        BALANCES.save(storage, (player, &amount.denom), &remaining)?;
+    }
+    Ok(())
+}
+
+pub fn try_placebet(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    host: Addr,
+    opponent: Addr,
+    outcome: GameResult,
+) -> Result<Response, ContractError> {
+    let stake = one_coin(&info)?;
+
+    // players would know (or decide) the outcome, so they're out
+    if info.sender == host || info.sender == opponent {
+        return Err(ContractError::PlayerCannotBet {});
+    }
+
+    let game = games()
+        .may_load(deps.storage, (host.clone(), opponent.clone()))?
+        .ok_or(ContractError::GameNotFound {})?;
+
+    // no more bets once the opponent has moved (in any round) or the game is over
+    // a minus one game closes as soon as anyone committed their moves
+    // a plain game never opens, the opponent could bet on the result they pick
+    let minus_one_started = game.minus_one.as_ref().is_some_and(|minus_one| {
+        minus_one.phase != MinusOnePhase::CommitMoves
+            || minus_one.host.commitment.is_some()
+            || minus_one.opponent.commitment.is_some()
+    });
+    if game.host_move.is_some()
+        || game.opponent_move.is_some()
+        || minus_one_started
+        || game.score != Score::default()
+        || game.expires.is_expired(&env.block)
+    {
+        return Err(ContractError::BettingClosed {});
+    }
+
+    // first bet decides the denom of the pool
+    let mut pool = BET_POOLS
+        .may_load(deps.storage, (&host, &opponent))?
+        .unwrap_or(BetPool {
+            denom: stake.denom.clone(),
+            host_wins: Uint128::zero(),
+            opponent_wins: Uint128::zero(),
+            tie: Uint128::zero(),
+            bettors: 0,
+        });
+    if pool.denom != stake.denom {
+        return Err(ContractError::WrongBetDenom { denom: pool.denom });
+    }
+
+    // topping up is fine, switching sides is not
+    let bet_key = (&host, &opponent, &info.sender);
+    let bet = match BETS.may_load(deps.storage, bet_key)? {
+        Some(bet) if bet.outcome != outcome => return Err(ContractError::ConflictingBet {}),
+        Some(bet) => Bet {
+            outcome: bet.outcome,
+            amount: bet.amount + stake.amount,
+        },
+        // the pool is settled in one go when the game ends, so it's capped
+        None if pool.bettors >= MAX_BETTORS => {
+            return Err(ContractError::TooManyBettors { max: MAX_BETTORS })
+        }
+        None => {
+            pool.bettors += 1;
+            Bet {
+                outcome: outcome.clone(),
+                amount: stake.amount,
+            }
+        }
+    };
+
+    pool.add(&outcome, stake.amount);
+    BET_POOLS.save(deps.storage, (&host, &opponent), &pool)?;
+    BETS.save(deps.storage, bet_key, &bet)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_placebet")
+        .add_attribute("bettor", info.sender)
+        .add_attribute("amount", stake.amount))
+}
+
+// pays out (and removes) the spectator pool of given game
+// winning bettors split the whole pool pro rata, if there's no result or nobody
+// backed the actual result, every bettor is refunded
+fn settle_bets(
+    storage: &mut dyn Storage,
+    game: &Game,
+    result: Option<&GameResult>,
+) -> StdResult<Vec<Payout>> {
+    let pool = match BET_POOLS.may_load(storage, (&game.host, &game.opponent))? {
+        Some(pool) => pool,
+        None => return Ok(vec![]),
+    };
+    let bets = BETS
+        .prefix((&game.host, &game.opponent))
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let total = pool.total();
+    let winning_total = result.map(|r| pool.on_outcome(r)).unwrap_or_default();
+
+    let mut payouts = vec![];
+    for (bettor, bet) in bets {
+        let payout = match result {
+            _ if winning_total.is_zero() => bet.amount,
+            Some(result) if &bet.outcome == result => {
+                bet.amount.multiply_ratio(total, winning_total)
+            }
+            _ => Uint128::zero(),
+        };
+        BETS.remove(storage, (&game.host, &game.opponent, &bettor));
+        if !payout.is_zero() {
+            payouts.push(Payout::Native {
+                recipient: bettor,
+                amount: coin(payout.u128(), &pool.denom),
+            });
+        }
+    }
+    BET_POOLS.remove(storage, (&game.host, &game.opponent));
+
+    Ok(payouts)
+}
+
+pub fn try_createroyale(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    entry_fee: Option<Coin>,
+    max_players: u32,
+    phase_timeout: Option<Duration>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    let phase_timeout = checked_timeout(&config, phase_timeout)?;
+    // whatever was sent along tops up the balance the entry fee is taken from
+    deposit_funds(deps.storage, &info.sender, &info.funds)?;
+    if !(MIN_ROYALE_PLAYERS..=MAX_ROYALE_PLAYERS).contains(&max_players) {
+        return Err(ContractError::InvalidPlayerCount {
+            min: MIN_ROYALE_PLAYERS,
+            max: MAX_ROYALE_PLAYERS,
+        });
+    }
+
+    let id = ROYALE_SEQ.may_load(deps.storage)?.unwrap_or_default() + 1;
+    ROYALE_SEQ.save(deps.storage, &id)?;
+    let royale = Royale {
+        host: info.sender.clone(),
+        entry_fee: entry_fee.filter(|fee| !fee.amount.is_zero()),
+        pot: Uint128::zero(),
+        max_players,
+        players: vec![],
+        status: RoyaleStatus::Open,
+        round: 0,
+        phase_timeout,
+        deadline: phase_timeout.after(&env.block),
+    };
+    let res = join_royale(deps.storage, &env, id, royale, info.sender)?;
+    Ok(res.add_attribute("method", "try_createroyale"))
+}
+
+pub fn try_joinroyale(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    deposit_funds(deps.storage, &info.sender, &info.funds)?;
+    let royale = load_royale(deps.storage, id)?;
+    let res = join_royale(deps.storage, &env, id, royale, info.sender)?;
+    Ok(res.add_attribute("method", "try_joinroyale"))
+}
+
+fn join_royale(
+    storage: &mut dyn Storage,
+    env: &Env,
+    id: u64,
+    mut royale: Royale,
+    player: Addr,
+) -> Result<Response, ContractError> {
+    assert_not_blacklisted(storage, env, &player)?;
+    assert_allowlisted(storage, &player)?;
+    assert_royale_status(&royale, RoyaleStatus::Open)?;
+    if royale.deadline.is_expired(&env.block) {
+        return Err(ContractError::GameExpired {});
+    }
+    if royale.players.contains(&player) {
+        return Err(ContractError::AlreadyJoined {});
+    }
+    if royale.players.len() >= royale.max_players as usize {
+        return Err(ContractError::RoyaleFull {});
+    }
+
+    if let Some(fee) = &royale.entry_fee {
+        debit_balance(storage, &player, fee)?;
+        royale.pot += fee.amount;
+    }
+    royale.players.push(player);
+    // starts on its own once full
+    if royale.players.len() == royale.max_players as usize {
+        begin_royale_round(&mut royale, env);
+    }
+    ROYALES.save(storage, id, &royale)?;
+
+    Ok(Response::new()
+        .add_attribute("royale_id", id.to_string())
+        .add_attribute("players", royale.players.len().to_string()))
+}
+
+pub fn try_startroyale(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut royale = load_royale(deps.storage, id)?;
+    if royale.host != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    assert_royale_status(&royale, RoyaleStatus::Open)?;
+    if royale.players.len() < MIN_ROYALE_PLAYERS as usize {
+        return Err(ContractError::InvalidPlayerCount {
+            min: MIN_ROYALE_PLAYERS,
+            max: MAX_ROYALE_PLAYERS,
+        });
+    }
+
+    begin_royale_round(&mut royale, &env);
+    ROYALES.save(deps.storage, id, &royale)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_startroyale")
+        .add_attribute("royale_id", id.to_string()))
+}
+
+pub fn try_commitroyalemove(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+    commitment: Binary,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let royale = load_royale(deps.storage, id)?;
+    assert_royale_status(&royale, RoyaleStatus::Commit)?;
+    if royale.deadline.is_expired(&env.block) {
+        return Err(ContractError::GameExpired {});
+    }
+    if !royale.players.contains(&info.sender) {
+        return Err(ContractError::NotInRoyale {});
+    }
+    if ROYALE_MOVES.has(deps.storage, (id, &info.sender)) {
+        return Err(ContractError::MoveAlreadyCommitted {});
+    }
+
+    let royale_move = RoyaleMove {
+        commitment,
+        game_move: None,
+    };
+    ROYALE_MOVES.save(deps.storage, (id, &info.sender), &royale_move)?;
+
+    // last one to commit moves the royale on to revealing
+    let committed = ROYALE_MOVES
+        .prefix(id)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .count();
+    let res = if committed == royale.players.len() {
+        progress_royale(deps.storage, &env, id, royale)?
+    } else {
+        Response::new()
+    };
+    Ok(res.add_attribute("method", "try_commitroyalemove"))
+}
+
+pub fn try_revealroyalemove(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+    game_move: GameMove,
+    salt: String,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let royale = load_royale(deps.storage, id)?;
+    assert_royale_status(&royale, RoyaleStatus::Reveal)?;
+    if royale.deadline.is_expired(&env.block) {
+        return Err(ContractError::GameExpired {});
+    }
+    if !royale.players.contains(&info.sender) {
+        return Err(ContractError::NotInRoyale {});
+    }
+    let mut royale_move = ROYALE_MOVES
+        .may_load(deps.storage, (id, &info.sender))?
+        .ok_or(ContractError::MoveNotCommitted {})?;
+    if royale_move.game_move.is_some() || !verify_move(&royale_move.commitment, &game_move, &salt) {
+        return Err(ContractError::InvalidCommitment {});
+    }
+    royale_move.game_move = Some(game_move);
+    ROYALE_MOVES.save(deps.storage, (id, &info.sender), &royale_move)?;
+
+    // last one to reveal resolves the round
+    let revealed = ROYALE_MOVES
+        .prefix(id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .filter(|item| matches!(item, Ok((_, royale_move)) if royale_move.game_move.is_some()))
+        .count();
+    let res = if revealed == royale.players.len() {
+        progress_royale(deps.storage, &env, id, royale)?
+    } else {
+        Response::new()
+    };
+    Ok(res.add_attribute("method", "try_revealroyalemove"))
+}
+
+// after a deadline, whoever didn't commit or reveal in time forfeits
+// a royale that wasn't started in time is cancelled and the entry fees refunded
+pub fn try_advanceroyale(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let royale = load_royale(deps.storage, id)?;
+    if !royale.deadline.is_expired(&env.block) {
+        return Err(ContractError::GameNotExpired {});
+    }
+
+    let res = if royale.status == RoyaleStatus::Open {
+        if let Some(fee) = &royale.entry_fee {
+            for player in &royale.players {
+                credit_balance(deps.storage, player, fee)?;
+            }
+        }
+        ROYALES.remove(deps.storage, id);
+        Response::new()
+            .add_attribute("royale_id", id.to_string())
+            .add_attribute("status", "cancelled")
+    } else {
+        progress_royale(deps.storage, &env, id, royale)?
+    };
+    Ok(res.add_attribute("method", "try_advanceroyale"))
+}
+
+fn load_royale(storage: &dyn Storage, id: u64) -> Result<Royale, ContractError> {
+    ROYALES
+        .may_load(storage, id)?
+        .ok_or(ContractError::GameNotFound {})
+}
+
+fn assert_royale_status(royale: &Royale, status: RoyaleStatus) -> Result<(), ContractError> {
+    if royale.status != status {
+        return Err(ContractError::WrongRoyaleStatus {
+            status: royale.status.clone(),
+        });
+    }
+    Ok(())
+}
+
+fn begin_royale_round(royale: &mut Royale, env: &Env) {
+    royale.status = RoyaleStatus::Commit;
+    royale.round += 1;
+    royale.deadline = royale.phase_timeout.after(&env.block);
+}
+
+// ends the current phase, players who didn't act are out
+// if nobody acted at all, the remaining players split the pot
+fn progress_royale(
+    storage: &mut dyn Storage,
+    env: &Env,
+    id: u64,
+    mut royale: Royale,
+) -> Result<Response, ContractError> {
+    let moves = ROYALE_MOVES
+        .prefix(id)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let res = Response::new()
+        .add_attribute("royale_id", id.to_string())
+        .add_attribute("round", royale.round.to_string());
+
+    match royale.status {
+        RoyaleStatus::Commit => {
+            if moves.is_empty() {
+                let players = royale.players.clone();
+                return finish_royale(storage, id, royale, players, res);
+            }
+            royale
+                .players
+                .retain(|player| moves.iter().any(|(committed, _)| committed == player));
+        }
+        RoyaleStatus::Reveal => {
+            let revealed: Vec<(Addr, GameMove)> = moves
+                .into_iter()
+                .filter_map(|(player, royale_move)| royale_move.game_move.map(|m| (player, m)))
+                .collect();
+            if revealed.is_empty() {
+                let players = royale.players.clone();
+                return finish_royale(storage, id, royale, players, res);
+            }
+            let losing = royale_losing_move(&revealed)?;
+            royale.players.retain(|player| {
+                revealed
+                    .iter()
+                    .any(|(p, m)| p == player && Some(m) != losing.as_ref())
+            });
+            clear_royale_moves(storage, id)?;
+        }
+        RoyaleStatus::Open => {
+            return Err(ContractError::WrongRoyaleStatus {
+                status: royale.status,
+            })
+        }
+    }
+
+    if royale.players.len() == 1 {
+        let players = royale.players.clone();
+        return finish_royale(storage, id, royale, players, res);
+    }
+    if royale.status == RoyaleStatus::Commit {
+        royale.status = RoyaleStatus::Reveal;
+        royale.deadline = royale.phase_timeout.after(&env.block);
+    } else {
+        begin_royale_round(&mut royale, env);
+    }
+    ROYALES.save(storage, id, &royale)?;
+
+    Ok(res.add_attribute("players", royale.players.len().to_string()))
+}
+
+// with exactly two different moves in the round, the beaten one is out
+// with one or all three of them nobody is
+fn royale_losing_move(revealed: &[(Addr, GameMove)]) -> Result<Option<GameMove>, ContractError> {
+    let mut present: Vec<&GameMove> = vec![];
+    for (_, game_move) in revealed {
+        if !present.contains(&game_move) {
+            present.push(game_move);
+        }
+    }
+    if present.len() != 2 {
+        return Ok(None);
+    }
+    Ok(match round_result(present[0], present[1])? {
+        GameResult::HostWins {} => Some(present[1].clone()),
+        GameResult::OpponentWins {} => Some(present[0].clone()),
+        GameResult::Tie {} => None,
+    })
+}
+
+fn clear_royale_moves(storage: &mut dyn Storage, id: u64) -> StdResult<()> {
+    let players = ROYALE_MOVES
+        .prefix(id)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for player in players {
+        ROYALE_MOVES.remove(storage, (id, &player));
+    }
+    Ok(())
+}
+
+// the last survivor takes the pot (minus the house fee), several winners split it evenly
+fn finish_royale(
+    storage: &mut dyn Storage,
+    id: u64,
+    royale: Royale,
+    winners: Vec<Addr>,
+    res: Response,
+) -> Result<Response, ContractError> {
+    clear_royale_moves(storage, id)?;
+    ROYALES.remove(storage, id);
+
+    if let Some(fee) = &royale.entry_fee {
+        let mut pot = royale.pot;
+        if winners.len() == 1 {
+            let config = CONFIG.may_load(storage)?.unwrap_or_default();
+            let house_fee = pot.multiply_ratio(config.fee_bps, 10_000u128);
+            if !house_fee.is_zero() {
+                TREASURY.update(storage, &fee.denom, |collected| -> StdResult<_> {
+                    Ok(collected.unwrap_or_default() + house_fee)
+                })?;
+                pot -= house_fee;
+            }
+        }
+        // whatever can't be split evenly goes to the first one
+        let share = pot.u128() / winners.len() as u128;
+        let remainder = pot.u128() - share * winners.len() as u128;
+        for (i, winner) in winners.iter().enumerate() {
+            let amount = if i == 0 { share + remainder } else { share };
+            credit_balance(storage, winner, &coin(amount, &fee.denom))?;
+        }
+    }
+
+    let res = res.add_attribute("status", "finished");
+    Ok(match winners.as_slice() {
+        [winner] => res.add_attribute("royale_winner", winner),
+        _ => res.add_attribute("royale_split", winners.len().to_string()),
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn try_createteamgame(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    host_team: Vec<Addr>,
+    opponent_team: Vec<Addr>,
+    wager: Option<Coin>,
+    tie_break: TieBreak,
+    phase_timeout: Option<Duration>,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    let phase_timeout = checked_timeout(&config, phase_timeout)?;
+    if host_team.is_empty()
+        || host_team.len() > MAX_TEAM_SIZE
+        || host_team.len() != opponent_team.len()
+    {
+        return Err(ContractError::InvalidTeams { max: MAX_TEAM_SIZE });
+    }
+    let mut members = vec![];
+    for member in host_team.iter().chain(opponent_team.iter()) {
+        let member = deps.api.addr_validate(member.as_str())?;
+        if members.contains(&member) {
+            return Err(ContractError::InvalidTeams { max: MAX_TEAM_SIZE });
+        }
+        members.push(member);
+    }
+    if !host_team.contains(&info.sender) {
+        return Err(ContractError::NotInTeam {});
+    }
+
+    let id = TEAM_GAME_SEQ.may_load(deps.storage)?.unwrap_or_default() + 1;
+    TEAM_GAME_SEQ.save(deps.storage, &id)?;
+    let team_game = TeamGame {
+        host_team,
+        opponent_team,
+        wager: wager.filter(|wager| !wager.amount.is_zero()),
+        pot: Uint128::zero(),
+        tie_break,
+        votes: vec![],
+        status: TeamGameStatus::Commit,
+        phase_timeout,
+        deadline: phase_timeout.after(&env.block),
+    };
+    TEAM_GAMES.save(deps.storage, id, &team_game)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_createteamgame")
+        .add_attribute("team_game_id", id.to_string()))
+}
+
+pub fn try_committeamvote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+    commitment: Binary,
+) -> Result<Response, ContractError> {
+    // whatever was sent along tops up the balance the wager is taken from
+    deposit_funds(deps.storage, &info.sender, &info.funds)?;
+    let mut team_game = load_team_game(deps.storage, id)?;
+    assert_team_game_status(&team_game, TeamGameStatus::Commit)?;
+    if team_game.deadline.is_expired(&env.block) {
+        return Err(ContractError::GameExpired {});
+    }
+    if !team_game.host_team.contains(&info.sender)
+        && !team_game.opponent_team.contains(&info.sender)
+    {
+        return Err(ContractError::NotInTeam {});
+    }
+    if team_game
+        .votes
+        .iter()
+        .any(|vote| vote.member == info.sender)
+    {
+        return Err(ContractError::MoveAlreadyCommitted {});
+    }
+    assert_not_blacklisted(deps.storage, &env, &info.sender)?;
+    assert_allowlisted(deps.storage, &info.sender)?;
+
+    if let Some(wager) = &team_game.wager {
+        debit_balance(deps.storage, &info.sender, wager)?;
+        team_game.pot += wager.amount;
+    }
+    team_game.votes.push(TeamVote {
+        member: info.sender,
+        commitment,
+        game_move: None,
+    });
+
+    // last member to vote moves the game on to revealing
+    let members = team_game.host_team.len() + team_game.opponent_team.len();
+    let res = if team_game.votes.len() == members {
+        progress_team_game(deps.storage, &env, id, team_game)?
+    } else {
+        TEAM_GAMES.save(deps.storage, id, &team_game)?;
+        Response::new()
+    };
+    Ok(res.add_attribute("method", "try_committeamvote"))
+}
+
+pub fn try_revealteamvote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+    game_move: GameMove,
+    salt: String,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut team_game = load_team_game(deps.storage, id)?;
+    assert_team_game_status(&team_game, TeamGameStatus::Reveal)?;
+    if team_game.deadline.is_expired(&env.block) {
+        return Err(ContractError::GameExpired {});
+    }
+    let vote = team_game
+        .votes
+        .iter_mut()
+        .find(|vote| vote.member == info.sender)
+        .ok_or(ContractError::MoveNotCommitted {})?;
+    if vote.game_move.is_some() || !verify_move(&vote.commitment, &game_move, &salt) {
+        return Err(ContractError::InvalidCommitment {});
+    }
+    vote.game_move = Some(game_move);
+
+    // last member to reveal resolves the game
+    let res = if team_game.votes.iter().all(|vote| vote.game_move.is_some()) {
+        progress_team_game(deps.storage, &env, id, team_game)?
+    } else {
+        TEAM_GAMES.save(deps.storage, id, &team_game)?;
+        Response::new()
+    };
+    Ok(res.add_attribute("method", "try_revealteamvote"))
+}
+
+pub fn try_advanceteamgame(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let team_game = load_team_game(deps.storage, id)?;
+    if !team_game.deadline.is_expired(&env.block) {
+        return Err(ContractError::GameNotExpired {});
+    }
+    let res = progress_team_game(deps.storage, &env, id, team_game)?;
+    Ok(res.add_attribute("method", "try_advanceteamgame"))
+}
+
+fn load_team_game(storage: &dyn Storage, id: u64) -> Result<TeamGame, ContractError> {
+    TEAM_GAMES
+        .may_load(storage, id)?
+        .ok_or(ContractError::GameNotFound {})
+}
+
+fn assert_team_game_status(
+    team_game: &TeamGame,
+    status: TeamGameStatus,
+) -> Result<(), ContractError> {
+    if team_game.status != status {
+        return Err(ContractError::WrongTeamGameStatus {
+            status: team_game.status.clone(),
+        });
+    }
+    Ok(())
+}
+
+// ends the commit phase, or resolves the game after the reveal phase
+fn progress_team_game(
+    storage: &mut dyn Storage,
+    env: &Env,
+    id: u64,
+    mut team_game: TeamGame,
+) -> Result<Response, ContractError> {
+    let res = Response::new().add_attribute("team_game_id", id.to_string());
+    if team_game.status == TeamGameStatus::Commit && !team_game.votes.is_empty() {
+        team_game.status = TeamGameStatus::Reveal;
+        team_game.deadline = team_game.phase_timeout.after(&env.block);
+        TEAM_GAMES.save(storage, id, &team_game)?;
+        return Ok(res.add_attribute("status", "reveal"));
+    }
+    TEAM_GAMES.remove(storage, id);
+
+    // a team without a move loses, two teams without one tie
+    let host_move = team_move(&team_game.host_team, &team_game.votes, &team_game.tie_break);
+    let opponent_move = team_move(
+        &team_game.opponent_team,
+        &team_game.votes,
+        &team_game.tie_break,
+    );
+    let result = match (&host_move, &opponent_move) {
+        (Some(host_move), Some(opponent_move)) => round_result(host_move, opponent_move)?,
+        (Some(_), None) => GameResult::HostWins {},
+        (None, Some(_)) => GameResult::OpponentWins {},
+        (None, None) => GameResult::Tie {},
+    };
+    let winning_team = match result {
+        GameResult::HostWins {} => Some(&team_game.host_team),
+        GameResult::OpponentWins {} => Some(&team_game.opponent_team),
+        GameResult::Tie {} => None,
+    };
+
+    if let Some(wager) = &team_game.wager {
+        // members who committed but never revealed lose their stake
+        let revealed: Vec<&Addr> = team_game
+            .votes
+            .iter()
+            .filter(|vote| vote.game_move.is_some())
+            .map(|vote| &vote.member)
+            .collect();
+        match winning_team {
+            // members who revealed split the pot (minus the house fee)
+            Some(team) => {
+                let winners: Vec<&Addr> = revealed
+                    .into_iter()
+                    .filter(|member| team.contains(member))
+                    .collect();
+                let config = CONFIG.may_load(storage)?.unwrap_or_default();
+                let fee = team_game.pot.multiply_ratio(config.fee_bps, 10_000u128);
+                if !fee.is_zero() {
+                    TREASURY.update(storage, &wager.denom, |collected| -> StdResult<_> {
+                        Ok(collected.unwrap_or_default() + fee)
+                    })?;
+                }
+                let pot = (team_game.pot - fee).u128();
+                // whatever can't be split evenly goes to the first one
+                let share = pot / winners.len() as u128;
+                let remainder = pot - share * winners.len() as u128;
+                for (i, winner) in winners.into_iter().enumerate() {
+                    let amount = if i == 0 { share + remainder } else { share };
+                    credit_balance(storage, winner, &coin(amount, &wager.denom))?;
+                }
+            }
+            // everyone who revealed gets their stake back, the rest goes to the treasury
+            None => {
+                for member in &revealed {
+                    credit_balance(storage, member, wager)?;
+                }
+                let forfeited =
+                    team_game.pot - wager.amount * Uint128::from(revealed.len() as u128);
+                if !forfeited.is_zero() {
+                    TREASURY.update(storage, &wager.denom, |collected| -> StdResult<_> {
+                        Ok(collected.unwrap_or_default() + forfeited)
+                    })?;
+                }
+            }
+        }
+    }
+
+    let mut res = res
+        .add_attribute("status", "finished")
+        .add_attribute("result", result_string(&result));
+    for (team, team_move) in [
+        ("host_team_move", host_move),
+        ("opponent_team_move", opponent_move),
+    ] {
+        if let Some(team_move) = team_move {
+            res = res.add_attribute(team, team_move.as_str());
+        }
+    }
+    Ok(res)
+}
+
+// the move most of the team's revealed votes went to, if there is one
+// a member who committed but didn't reveal could otherwise pick the majority after
+// seeing the other votes, so a single unrevealed vote makes the team forfeit
+fn team_move(team: &[Addr], votes: &[TeamVote], tie_break: &TieBreak) -> Option<GameMove> {
+    let unrevealed = votes
+        .iter()
+        .any(|vote| vote.game_move.is_none() && team.contains(&vote.member));
+    if unrevealed {
+        return None;
+    }
+    let revealed: Vec<(&Addr, &GameMove)> = team
+        .iter()
+        .filter_map(|member| {
+            votes
+                .iter()
+                .find(|vote| &vote.member == member)
+                .and_then(|vote| vote.game_move.as_ref())
+                .map(|game_move| (member, game_move))
+        })
+        .collect();
+    let count = |game_move: &GameMove| revealed.iter().filter(|(_, m)| *m == game_move).count();
+    let most = revealed.iter().map(|(_, m)| count(m)).max()?;
+    let mut tied: Vec<&GameMove> = vec![];
+    for (_, game_move) in &revealed {
+        if count(game_move) == most && !tied.contains(game_move) {
+            tied.push(game_move);
+        }
+    }
+
+    match (tied.as_slice(), tie_break) {
+        ([game_move], _) => Some((*game_move).clone()),
+        // revealed votes are in team order, so the first tied one is the captain's pick
+        (tied, TieBreak::Captain {}) => tied.first().map(|game_move| (*game_move).clone()),
+        (_, TieBreak::Forfeit {}) => None,
+    }
+}
+
+pub fn try_updatebeacon(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    beacon: BeaconConfig,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    if beacon.period == 0 || beacon.pubkey.len() != 48 {
+        return Err(ContractError::InvalidBeaconConfig {});
+    }
+
+    // open games keep the key they started with, see BeaconRound
+    let mut config = CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    config.beacon = Some(beacon.clone());
+    CONFIG.save(deps.storage, &config)?;
+    let params = vec![
+        attr("pubkey", beacon.pubkey.to_base64()),
+        attr("genesis", beacon.genesis.seconds().to_string()),
+        attr("period", beacon.period.to_string()),
+    ];
+    audit(
+        deps.storage,
+        &env,
+        &info.sender,
+        "update_beacon",
+        params.clone(),
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_updatebeacon")
+        .add_attributes(params))
+}
+
+pub fn try_updatehouse(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    house: HouseConfig,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    if house.edge_bps > 10_000 || house.max_wager_bps > 10_000 {
+        return Err(ContractError::InvalidHouseConfig {});
+    }
+
+    let mut config = CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    config.house = Some(house.clone());
+    CONFIG.save(deps.storage, &config)?;
+    let params = vec![
+        attr("open", house.open.to_string()),
+        attr("edge_bps", house.edge_bps.to_string()),
+        attr("max_wager_bps", house.max_wager_bps.to_string()),
+    ];
+    audit(
+        deps.storage,
+        &env,
+        &info.sender,
+        "update_house",
+        params.clone(),
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_updatehouse")
+        .add_attributes(params))
+}
+
+pub fn try_fundbankroll(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    if info.funds.is_empty() {
+        return Err(PaymentError::NoFunds {}.into());
+    }
+
+    for fund in &info.funds {
+        BANKROLL.update(deps.storage, &fund.denom, |bankroll| -> StdResult<_> {
+            Ok(bankroll.unwrap_or_default() + fund.amount)
+        })?;
+    }
+    let amount = info
+        .funds
+        .iter()
+        .map(Coin::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+    let params = vec![attr("amount", amount)];
+    audit(
+        deps.storage,
+        &env,
+        &info.sender,
+        "fund_bankroll",
+        params.clone(),
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_fundbankroll")
+        .add_attributes(params))
+}
+
+// only the free part of the bankroll can be withdrawn, not what's at stake in open games
+pub fn try_withdrawbankroll(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Coin,
+    recipient: Addr,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let checked_recipient = deps.api.addr_validate(recipient.as_ref())?;
+
+    let bankroll = BANKROLL
+        .may_load(deps.storage, &amount.denom)?
+        .unwrap_or_default();
+    if bankroll < amount.amount {
+        return Err(ContractError::InsufficientBalance {
+            denom: amount.denom,
+            available: bankroll,
+        });
+    }
+    BANKROLL.save(deps.storage, &amount.denom, &(bankroll - amount.amount))?;
+    let params = vec![
+        attr("amount", amount.to_string()),
+        attr("recipient", &checked_recipient),
+    ];
+    audit(
+        deps.storage,
+        &env,
+        &info.sender,
+        "withdraw_bankroll",
+        params,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_withdrawbankroll")
+        .add_attribute("recipient", checked_recipient.clone())
+        .add_message(BankMsg::Send {
+            to_address: checked_recipient.to_string(),
+            amount: vec![amount],
+        }))
+}
+
+// player's move is stored in plain, the house move is only decided by a beacon
+// that doesn't exist yet, so there's nothing to hide
+pub fn try_playhouse(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    player_move: GameMove,
+    wager: Coin,
+) -> Result<Response, ContractError> {
+    deposit_funds(deps.storage, &info.sender, &info.funds)?;
+    assert_not_blacklisted(deps.storage, &env, &info.sender)?;
+    assert_allowlisted(deps.storage, &info.sender)?;
+
+    let config = CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    let house = config
+        .house
+        .filter(|house| house.open)
+        .ok_or(ContractError::HouseClosed {})?;
+    let beacon_config = config.beacon.ok_or(ContractError::BeaconNotConfigured {})?;
+    if wager.amount.is_zero() {
+        return Err(ContractError::InvalidWager {});
+    }
+    if HOUSE_GAMES.has(deps.storage, &info.sender) {
+        return Err(ContractError::HouseGameInProgress {});
+    }
+
+    let bankroll = BANKROLL
+        .may_load(deps.storage, &wager.denom)?
+        .unwrap_or_default();
+    let max = bankroll.multiply_ratio(house.max_wager_bps, 10_000u128);
+    if wager.amount > max {
+        return Err(ContractError::WagerAboveHouseLimit { max });
+    }
+    debit_balance(deps.storage, &info.sender, &wager)?;
+
+    // house puts up the winnings minus its edge, wager never exceeds the bankroll
+    let reserved = wager.amount - wager.amount.multiply_ratio(house.edge_bps, 10_000u128);
+    BANKROLL.save(deps.storage, &wager.denom, &(bankroll - reserved))?;
+
+    let game = HouseGame {
+        player: info.sender,
+        player_move,
+        wager,
+        reserved,
+        beacon: upcoming_beacon(&beacon_config, &env),
+        expires: DEFAULT_GAME_TIMEOUT.after(&env.block),
+    };
+    HOUSE_GAMES.save(deps.storage, &game.player, &game)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_playhouse")
+        .add_attribute("player", game.player)
+        .add_attribute("round", game.beacon.round.to_string()))
+}
+
+// first drand round published after this block, checked against the key configured now
+fn upcoming_beacon(beacon: &BeaconConfig, env: &Env) -> BeaconRound {
+    BeaconRound {
+        pubkey: beacon.pubkey.clone(),
+        round: next_round(beacon.genesis, beacon.period, env.block.time),
+    }
+}
+
+// randomness of a submitted beacon, if it's the one the game waits for
+fn verify_beacon_round(
+    beacon: &BeaconRound,
+    round: u64,
+    previous_signature: &[u8],
+    signature: &[u8],
+) -> Result<[u8; 32], ContractError> {
+    if round != beacon.round {
+        return Err(ContractError::WrongBeaconRound {
+            expected: beacon.round,
+        });
+    }
+    if !verify_beacon(&beacon.pubkey, round, previous_signature, signature) {
+        return Err(ContractError::InvalidBeacon {});
+    }
+    Ok(beacon_randomness(signature))
+}
+
+// beacons are public, so anyone (usually the winner) can settle the game
+pub fn try_submitbeacon(
+    deps: DepsMut,
+    info: MessageInfo,
+    player: Addr,
+    round: u64,
+    previous_signature: Binary,
+    signature: Binary,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let game = HOUSE_GAMES
+        .may_load(deps.storage, &player)?
+        .ok_or(ContractError::GameNotFound {})?;
+    let randomness = verify_beacon_round(&game.beacon, round, &previous_signature, &signature)?;
+
+    let house_move = house_move(&randomness, &game.player);
+    let result = round_result(&house_move, &game.player_move)?;
+    let stakes = game.wager.amount + game.reserved;
+    let (player_share, house_share, result) = match result {
+        GameResult::OpponentWins {} => (stakes, Uint128::zero(), "Player won"),
+        GameResult::Tie {} => (game.wager.amount, game.reserved, "Tie"),
+        GameResult::HostWins {} => (Uint128::zero(), stakes, "House won"),
+    };
+    if !player_share.is_zero() {
+        let payout = coin(player_share.u128(), &game.wager.denom);
+        credit_balance(deps.storage, &game.player, &payout)?;
+    }
+    BANKROLL.update(
+        deps.storage,
+        &game.wager.denom,
+        |bankroll| -> StdResult<_> { Ok(bankroll.unwrap_or_default() + house_share) },
+    )?;
+    HOUSE_GAMES.remove(deps.storage, &game.player);
+
+    Ok(Response::new()
+        .add_attribute("method", "try_submitbeacon")
+        .add_attribute("player", game.player)
+        .add_attribute("house_move", house_move.as_str())
+        .add_attribute("game_result", result))
+}
+
+// the house should settle the games it wins, a game nobody settled in time is called off:
+// the player gets the wager back and what the house put up returns to the bankroll
+pub fn try_expirehousegame(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    player: Addr,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let game = HOUSE_GAMES
+        .may_load(deps.storage, &player)?
+        .ok_or(ContractError::GameNotFound {})?;
+    if !game.expires.is_expired(&env.block) {
+        return Err(ContractError::GameNotExpired {});
+    }
+
+    credit_balance(deps.storage, &game.player, &game.wager)?;
+    BANKROLL.update(
+        deps.storage,
+        &game.wager.denom,
+        |bankroll| -> StdResult<_> { Ok(bankroll.unwrap_or_default() + game.reserved) },
+    )?;
+    HOUSE_GAMES.remove(deps.storage, &game.player);
+
+    Ok(Response::new()
+        .add_attribute("method", "try_expirehousegame")
+        .add_attribute("player", game.player))
+}
+
+// one beacon settles every house game of its round, mixing in the player
+// keeps their house moves independent
+fn house_move(randomness: &[u8], player: &Addr) -> GameMove {
+    let mut hasher = Sha256::new();
+    hasher.update(randomness);
+    hasher.update(player.as_bytes());
+    let mut value = [0u8; 8];
+    value.copy_from_slice(&hasher.finalize()[..8]);
+    match u64::from_be_bytes(value) % 3 {
+        0 => GameMove::Rock {},
+        1 => GameMove::Paper {},
+        _ => GameMove::Scissors {},
+    }
+}
+
+pub fn try_blockplayer(
+    deps: DepsMut,
+    info: MessageInfo,
+    addr: Addr,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let checked_addr = deps.api.addr_validate(addr.as_ref())?;
+    BLOCKS.save(deps.storage, (&info.sender, &checked_addr), &Empty {})?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_blockplayer")
+        .add_attribute("addr", checked_addr))
+}
+
+pub fn try_unblockplayer(
+    deps: DepsMut,
+    info: MessageInfo,
+    addr: Addr,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let checked_addr = deps.api.addr_validate(addr.as_ref())?;
+
+    if !BLOCKS.has(deps.storage, (&info.sender, &checked_addr)) {
+        return Err(ContractError::NotBlocked { addr: checked_addr });
+    }
+    BLOCKS.remove(deps.storage, (&info.sender, &checked_addr));
+
+    Ok(Response::new()
+        .add_attribute("method", "try_unblockplayer")
+        .add_attribute("addr", checked_addr))
+}
+
+pub fn try_approveoperator(
+    deps: DepsMut,
+    info: MessageInfo,
+    operator: Addr,
+    max_wager: Option<Coin>,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let checked_operator = deps.api.addr_validate(operator.as_ref())?;
+    if checked_operator == info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let approval = Approval {
+        max_wager,
+        expires: expires.unwrap_or_default(),
+    };
+    OPERATORS.save(deps.storage, (&info.sender, &checked_operator), &approval)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_approveoperator")
+        .add_attribute("operator", checked_operator))
+}
+
+pub fn try_revokeoperator(
+    deps: DepsMut,
+    info: MessageInfo,
+    operator: Addr,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let checked_operator = deps.api.addr_validate(operator.as_ref())?;
+
+    if !OPERATORS.has(deps.storage, (&info.sender, &checked_operator)) {
+        return Err(ContractError::NotOperator {
+            player: info.sender,
+        });
+    }
+    OPERATORS.remove(deps.storage, (&info.sender, &checked_operator));
+
+    Ok(Response::new()
+        .add_attribute("method", "try_revokeoperator")
+        .add_attribute("operator", checked_operator))
+}
+
+// operator's message runs as if the player sent it
+pub fn try_executeas(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    player: Addr,
+    msg: ExecuteMsg,
+) -> Res<Response> {
+    // operator's funds would end up in the player's balance
+    nonpayable(&info).map_err(ContractError::from)?;
+    let checked_player = deps.api.addr_validate(player.as_ref())?;
+
+    let approval = OPERATORS
+        .may_load(deps.storage, (&checked_player, &info.sender))?
+        .filter(|approval| !approval.expires.is_expired(&env.block))
+        .ok_or_else(|| ContractError::NotOperator {
+            player: checked_player.clone(),
+        })?;
+    let wager = operator_wager(deps.as_ref(), &checked_player, &msg)?;
+    if let (Some(max), Some(wager)) = (approval.max_wager, wager) {
+        if max.denom != wager.denom || max.amount < wager.amount {
+            return Err(ContractError::WagerAboveOperatorLimit { max }.into());
+        }
+    }
+
+    let player_info = MessageInfo {
+        sender: checked_player,
+        funds: vec![],
+    };
+    let res = execute(deps, env, player_info, msg)?;
+    Ok(res.add_attribute("operator", info.sender))
+}
+
+// what the player puts up with msg, fails for anything an operator can't do
+fn operator_wager(
+    deps: Deps,
+    player: &Addr,
+    msg: &ExecuteMsg,
+) -> Result<Option<Coin>, ContractError> {
+    match msg {
+        ExecuteMsg::ProposeGame { terms, .. } => Ok(terms.wager.clone()),
+        // answering matches the host's wager
+        ExecuteMsg::EndGame { host, .. } | ExecuteMsg::AcceptChallenge { host } => Ok(games()
+            .may_load(deps.storage, (host.clone(), player.clone()))?
+            .and_then(|game| game.wager)),
+        // stakes are already in the game
+        ExecuteMsg::DeclineChallenge { .. }
+        | ExecuteMsg::CommitMove { .. }
+        | ExecuteMsg::CommitMinusOne { .. }
+        | ExecuteMsg::RevealMinusOne { .. }
+        | ExecuteMsg::CommitWithdrawal { .. }
+        | ExecuteMsg::RevealWithdrawal { .. }
+        | ExecuteMsg::RevealMove { .. } => Ok(None),
+        ExecuteMsg::CommitChallenge { wager, .. } => Ok(wager.clone()),
+        _ => Err(ContractError::NotOperatorAction {}),
+    }
+}
+
+pub fn try_proposeadmin(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    admin: Addr,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let new_admin = deps.api.addr_validate(admin.as_ref())?;
+    if let Some(expires) = &expires {
+        if expires.is_expired(&env.block) {
+            return Err(ContractError::AdminProposalExpired {});
+        }
+    }
+
+    let mut params = vec![attr("new_admin", &new_admin)];
+    if let Some(expires) = &expires {
+        params.push(attr("expires", expires.to_string()));
+    }
+    audit(deps.storage, &env, &info.sender, "propose_admin", params)?;
+
+    // a new proposal replaces the previous one
+    PENDING_ADMIN.save(
+        deps.storage,
+        &PendingAdmin {
+            new_admin: new_admin.clone(),
+            expires,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_proposeadmin")
+        .add_attribute("new_admin", new_admin))
+}
+
+pub fn try_acceptadmin(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let pending = PENDING_ADMIN
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingAdmin {})?;
+
+    if pending.new_admin != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if let Some(expires) = &pending.expires {
+        if expires.is_expired(&env.block) {
+            return Err(ContractError::AdminProposalExpired {});
+        }
+    }
+
+    PENDING_ADMIN.remove(deps.storage);
+    ADMIN.set(deps.branch(), Some(pending.new_admin.clone()))?;
+    audit(deps.storage, &env, &info.sender, "accept_admin", vec![])?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_acceptadmin")
+        .add_attribute("admin", pending.new_admin))
+}
+
+pub fn try_canceladminproposal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let pending = PENDING_ADMIN
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingAdmin {})?;
+    PENDING_ADMIN.remove(deps.storage);
+    let params = vec![attr("new_admin", pending.new_admin)];
+    audit(
+        deps.storage,
+        &env,
+        &info.sender,
+        "cancel_admin_proposal",
+        params,
+    )?;
+
+    Ok(Response::new().add_attribute("method", "try_canceladminproposal"))
+}
+
+pub fn try_renounceadmin(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    // also drop any pending transfer, otherwise it could revive the admin
+    PENDING_ADMIN.remove(deps.storage);
+    ADMIN.set(deps.branch(), None)?;
+    audit(deps.storage, &env, &info.sender, "renounce_admin", vec![])?;
+
+    Ok(Response::new().add_attribute("method", "try_renounceadmin"))
+}
+
+pub fn try_addtoblacklist(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    addr: Addr,
+    reason: Option<String>,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    assert_role(deps.as_ref(), &info.sender, Role::Moderator)?;
+    let checked_addr = deps.api.addr_validate(addr.as_ref())?;
+
+    let mut params = vec![attr("addr", &checked_addr)];
+    if let Some(reason) = &reason {
+        params.push(attr("reason", reason));
+    }
+    if let Some(expires) = &expires {
+        params.push(attr("expires", expires.to_string()));
+    }
+    audit(deps.storage, &env, &info.sender, "add_to_blacklist", params)?;
+
+    let ban = BanRecord {
+        reason,
+        banned_by: info.sender,
+        banned_at: env.block.time,
+        expires,
+    };
+    BLACKLIST.save(deps.storage, &checked_addr, &ban)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_addtoblacklist")
+        .add_attribute("addr", checked_addr))
+}
+
+pub fn try_removefromblacklist(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    addr: Addr,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    assert_role(deps.as_ref(), &info.sender, Role::Moderator)?;
+    let checked_addr = deps.api.addr_validate(addr.as_ref())?;
+
+    if !BLACKLIST.has(deps.storage, &checked_addr) {
+        return Err(ContractError::NotBlacklisted { addr: checked_addr });
+    }
+    BLACKLIST.remove(deps.storage, &checked_addr);
+    let params = vec![attr("addr", &checked_addr)];
+    audit(
+        deps.storage,
+        &env,
+        &info.sender,
+        "remove_from_blacklist",
+        params,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_removefromblacklist")
+        .add_attribute("addr", checked_addr))
+}
+
+// helper that fails if addr has a ban that didn't lapse yet
+pub fn assert_not_blacklisted(
+    storage: &dyn Storage,
+    env: &Env,
+    addr: &Addr,
+) -> Result<(), ContractError> {
+    match BLACKLIST.may_load(storage, addr)? {
+        Some(ban) if ban.is_active(&env.block) => {
+            Err(ContractError::Blacklisted { addr: addr.clone() })
+        }
+        _ => Ok(()),
+    }
+}
+
+pub fn try_grantrole(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    role: Role,
+    addr: Addr,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let checked_addr = deps.api.addr_validate(addr.as_ref())?;
+    ROLES.save(deps.storage, (role.as_str(), &checked_addr), &Empty {})?;
+    let params = vec![attr("role", role.as_str()), attr("addr", &checked_addr)];
+    audit(deps.storage, &env, &info.sender, "grant_role", params)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_grantrole")
+        .add_attribute("role", role.as_str())
+        .add_attribute("addr", checked_addr))
+}
+
+pub fn try_revokerole(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    role: Role,
+    addr: Addr,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let checked_addr = deps.api.addr_validate(addr.as_ref())?;
+    ROLES.remove(deps.storage, (role.as_str(), &checked_addr));
+    let params = vec![attr("role", role.as_str()), attr("addr", &checked_addr)];
+    audit(deps.storage, &env, &info.sender, "revoke_role", params)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_revokerole")
+        .add_attribute("role", role.as_str())
+        .add_attribute("addr", checked_addr))
+}
+
+// helper that fails unless sender has given role (or is the admin)
+pub fn assert_role(deps: Deps, sender: &Addr, role: Role) -> Result<(), ContractError> {
+    if ADMIN.is_admin(deps, sender)? || ROLES.has(deps.storage, (role.as_str(), sender)) {
+        Ok(())
+    } else {
+        Err(ContractError::Unauthorized {})
     }
 }
 
-pub fn try_startgame(
+pub fn try_updatefee(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    opponent: Addr,
-    host_move: GameMove,
+    fee_bps: u16,
 ) -> Result<Response, ContractError> {
-    // check Addr
-    let checked_opponent: Addr = deps.api.addr_validate(&opponent.to_string())?;
+    nonpayable(&info)?;
+    assert_role(deps.as_ref(), &info.sender, Role::FeeManager)?;
 
-    // check that not trying to start game against oneself
-    if checked_opponent == info.sender {
-        return Err(ContractError::GameAgainstYourself {});
+    let mut config = CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    if fee_bps > config.max_fee_bps() {
+        return Err(ContractError::FeeAboveMax {
+            max: config.max_fee_bps(),
+        });
     }
+    config.fee_bps = fee_bps;
+    CONFIG.save(deps.storage, &config)?;
+    let params = vec![attr("fee_bps", fee_bps.to_string())];
+    audit(deps.storage, &env, &info.sender, "update_fee", params)?;
 
-    // grab the blacklist
-    let blacklist = BLACKLIST.query_hooks(deps.as_ref())?;
+    Ok(Response::new()
+        .add_attribute("method", "try_updatefee")
+        .add_attribute("fee_bps", fee_bps.to_string()))
+}
 
-    // check if opponent isn't blacklisted
-    if blacklist.hooks.contains(&checked_opponent.to_string()) {
-        return Err(ContractError::Blacklisted {
-            addr: checked_opponent,
+pub fn try_withdrawfees(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Coin,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    assert_role(deps.as_ref(), &info.sender, Role::FeeManager)?;
+    // the fee manager can't send fees anywhere else than the admin's treasury
+    let checked_recipient = CONFIG
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .treasury
+        .ok_or(ContractError::NoTreasury {})?;
+
+    let collected = TREASURY
+        .may_load(deps.storage, &amount.denom)?
+        .unwrap_or_default();
+    if collected < amount.amount {
+        return Err(ContractError::InsufficientBalance {
+            denom: amount.denom,
+            available: collected,
         });
     }
+    TREASURY.save(deps.storage, &amount.denom, &(collected - amount.amount))?;
+    let params = vec![
+        attr("amount", amount.to_string()),
+        attr("recipient", &checked_recipient),
+    ];
+    audit(deps.storage, &env, &info.sender, "withdraw_fees", params)?;
 
-    // check if this message sender (i.e. host) isn't blacklisted
-    if blacklist.hooks.contains(&info.sender.to_string()) {
-        return Err(ContractError::Blacklisted { addr: info.sender });
-    }
+    Ok(Response::new()
+        .add_attribute("method", "try_withdrawfees")
+        .add_attribute("recipient", checked_recipient.clone())
+        .add_message(BankMsg::Send {
+            to_address: checked_recipient.to_string(),
+            amount: vec![amount],
+        }))
+}
 
-    // create the Game struct from the submitted data
-    let game = Game {
-        host: info.sender.clone(),
-        opponent: checked_opponent.clone(),
-        host_move: host_move,
-        opponent_move: None, // first move by host = no move by opponent
-        result: None, // result only after opponent move
-    };
+#[allow(clippy::too_many_arguments)]
+pub fn try_updateconfig(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    allowlist_enabled: Option<bool>,
+    game_bond: Option<Coin>,
+    max_open_games: Option<u32>,
+    max_fee_bps: Option<u16>,
+    treasury: Option<Addr>,
+    timeout_limits: Option<TimeoutLimits>,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    if matches!(max_fee_bps, Some(max) if max > 10_000) {
+        return Err(ContractError::InvalidFee {});
+    }
+    // no timeout could be chosen with an empty range, and a zero one expires right away
+    if matches!(timeout_limits, Some(limits) if limits.min_blocks == 0
+        || limits.min_seconds == 0
+        || limits.min_blocks > limits.max_blocks
+        || limits.min_seconds > limits.max_seconds)
+    {
+        return Err(ContractError::InvalidTimeout {});
+    }
 
-    // save the new struct and create response accordingly
-    match save_game(deps, game) {
-        Ok(_game) => Ok(Response::new().add_attribute("method", "try_startgame")),
-        Err(e) => Err(e),
+    let mut config = CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    if let Some(allowlist_enabled) = allowlist_enabled {
+        config.allowlist_enabled = allowlist_enabled;
     }
-}
+    if let Some(game_bond) = game_bond {
+        config.game_bond = Some(game_bond).filter(|bond| !bond.amount.is_zero());
+    }
+    if let Some(max_open_games) = max_open_games {
+        config.max_open_games = Some(max_open_games).filter(|max| *max > 0);
+    }
+    if let Some(max_fee_bps) = max_fee_bps {
+        config.max_fee_bps = Some(max_fee_bps);
+        config.fee_bps = config.fee_bps.min(max_fee_bps);
+    }
+    if let Some(treasury) = treasury {
+        config.treasury = Some(deps.api.addr_validate(treasury.as_ref())?);
+    }
+    if let Some(timeout_limits) = timeout_limits {
+        config.timeout_limits = Some(timeout_limits);
+    }
+    CONFIG.save(deps.storage, &config)?;
 
-// helper function to create new game, wrapping IndexedMap update syntax
-pub fn save_game(deps: DepsMut, game: Game) -> Result<Game, ContractError> {
-    games().update(
+    let max_fee_bps = config.max_fee_bps();
+    let limits = config.timeout_limits();
+    let none = || "none".to_string();
+    let params = vec![
+        attr("allowlist_enabled", config.allowlist_enabled.to_string()),
+        attr(
+            "game_bond",
+            config.game_bond.map_or_else(none, |bond| bond.to_string()),
+        ),
+        attr(
+            "max_open_games",
+            config
+                .max_open_games
+                .map_or_else(none, |max| max.to_string()),
+        ),
+        attr("max_fee_bps", max_fee_bps.to_string()),
+        attr(
+            "treasury",
+            config.treasury.map_or_else(none, |addr| addr.to_string()),
+        ),
+        attr(
+            "timeout_blocks",
+            format!("{}-{}", limits.min_blocks, limits.max_blocks),
+        ),
+        attr(
+            "timeout_seconds",
+            format!("{}-{}", limits.min_seconds, limits.max_seconds),
+        ),
+    ];
+    audit(
         deps.storage,
-        generate_key_for_game(&game), // call helper function to get the key
-        |old| match old {
-            Some(_) => Err(ContractError::DuplicateGame {}), // if the game already exists, update() will fail here. prevents overwriting.
-            None => Ok(game), // if it doesn't exist, it's created
-        },
-    )
-}
+        &env,
+        &info.sender,
+        "update_config",
+        params.clone(),
+    )?;
 
-// helper function to update existing game, wrapping IndexedMap update sytax
-// unlike save_game(), this one will fail if the game doesn't exist yet
-pub fn update_game(deps: &mut DepsMut, game: Game) -> Result<Game, ContractError> {
-    games().update(
-        deps.storage,
-        generate_key_for_game(&game),
-        |old| match old {
-            Some(_) => Ok(game), // if game exists, update it
-            None => Err(ContractError::GameNotFound {}), // if game doesn't exist yet, fail
-        },
-    )
+    Ok(Response::new()
+        .add_attribute("method", "try_updateconfig")
+        .add_attributes(params))
 }
 
-// helper to wrap IndexedMap delete syntax
-pub fn delete_game(deps: &mut DepsMut, game: Game) -> Result<(), StdError> {
-    games().remove(deps.storage, generate_key_for_game(&game))
-}
+pub fn try_registerbot(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    addr: Addr,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let checked_addr = deps.api.addr_validate(addr.as_ref())?;
+    BOTS.save(deps.storage, &checked_addr, &Empty {})?;
+    let params = vec![attr("addr", &checked_addr)];
+    audit(deps.storage, &env, &info.sender, "register_bot", params)?;
 
-// helper that will create (host, opponent) tuple key from Game struct
-pub fn generate_key_for_game(game: &Game) -> (Addr, Addr) {
-    (game.host.clone(), game.opponent.clone())
+    Ok(Response::new()
+        .add_attribute("method", "try_registerbot")
+        .add_attribute("addr", checked_addr))
 }
 
-pub fn try_endgame(
-    mut deps: DepsMut,
+pub fn try_removebot(
+    deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    host: Addr,
-    opponent_move: GameMove,
+    addr: Addr,
 ) -> Result<Response, ContractError> {
-    // check Addr
-    let checked_host: Addr = deps.api.addr_validate(&host.to_string())?;
-    // might not be necessary to check host against blacklist, coz he couldn't have started the game if blacklisted
-    // however, he could have gotten blacklisted after starting the game...
-    let blacklist = BLACKLIST.query_hooks(deps.as_ref())?;
+    nonpayable(&info)?;
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let checked_addr = deps.api.addr_validate(addr.as_ref())?;
 
-    // check if host is not blacklisted (got blacklisted after the game was started)
-    if blacklist.hooks.contains(&checked_host.to_string()) {
-        return Err(ContractError::Blacklisted { addr: checked_host });
+    if !BOTS.has(deps.storage, &checked_addr) {
+        return Err(ContractError::NotABot { addr: checked_addr });
     }
+    BOTS.remove(deps.storage, &checked_addr);
+    let params = vec![attr("addr", &checked_addr)];
+    audit(deps.storage, &env, &info.sender, "remove_bot", params)?;
 
-    // check if this message sender isn't blacklisted
-    if blacklist.hooks.contains(&info.sender.to_string()) {
-        return Err(ContractError::Blacklisted { addr: info.sender });
-    }
+    Ok(Response::new()
+        .add_attribute("method", "try_removebot")
+        .add_attribute("addr", checked_addr))
+}
 
-    // lookup game by host, opponent
-    let opponent = info.sender;
-    let game_res = games().may_load(deps.storage, (checked_host, opponent));
-    let mut game = match game_res {
-        Ok(Some(game)) => game,
-        Ok(None) => return Err(ContractError::GameNotFound {}),
-        Err(_e) => return Err(ContractError::GameNotFound {}),
-    };
+pub fn try_addnftcollection(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    addr: Addr,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let checked_addr = deps.api.addr_validate(addr.as_ref())?;
+    NFT_COLLECTIONS.save(deps.storage, &checked_addr, &Empty {})?;
+    let params = vec![attr("addr", &checked_addr)];
+    audit(
+        deps.storage,
+        &env,
+        &info.sender,
+        "add_nft_collection",
+        params,
+    )?;
 
-    // find out game result
-    let result = match (&game.host_move, &opponent_move) {
-        // host starts with Rock
-        (GameMove::Rock {}, GameMove::Paper {}) => GameResult::OpponentWins {},
-        (GameMove::Rock {}, GameMove::Scissors {}) => GameResult::HostWins {},
+    Ok(Response::new()
+        .add_attribute("method", "try_addnftcollection")
+        .add_attribute("addr", checked_addr))
+}
 
-        // host starts with Paper
-        (GameMove::Paper {}, GameMove::Rock {}) => GameResult::HostWins {},
-        (GameMove::Paper {}, GameMove::Scissors {}) => GameResult::OpponentWins {},
+pub fn try_removenftcollection(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    addr: Addr,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let checked_addr = deps.api.addr_validate(addr.as_ref())?;
 
-        // host starts with Scissors
-        (GameMove::Scissors {}, GameMove::Rock {}) => GameResult::OpponentWins {},
-        (GameMove::Scissors {}, GameMove::Paper {}) => GameResult::HostWins {},
+    if !NFT_COLLECTIONS.has(deps.storage, &checked_addr) {
+        return Err(ContractError::UnknownNftCollection { addr: checked_addr });
+    }
+    NFT_COLLECTIONS.remove(deps.storage, &checked_addr);
+    let params = vec![attr("addr", &checked_addr)];
+    audit(
+        deps.storage,
+        &env,
+        &info.sender,
+        "remove_nft_collection",
+        params,
+    )?;
 
-        // same moves = tie
-        (move1, move2) if (move1 == move2) => GameResult::Tie {},
-        (_, _) => return Err(ContractError::GameResultNotFound {}),
-    };
+    Ok(Response::new()
+        .add_attribute("method", "try_removenftcollection")
+        .add_attribute("addr", checked_addr))
+}
 
-    // update map accordingly
-    game.opponent_move = Some(opponent_move);
-    game.result = Some(result.clone());
+pub fn try_addtoallowlist(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    addr: Addr,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let checked_addr = deps.api.addr_validate(addr.as_ref())?;
+    ALLOWLIST.save(deps.storage, &checked_addr, &Empty {})?;
+    let params = vec![attr("addr", &checked_addr)];
+    audit(deps.storage, &env, &info.sender, "add_to_allowlist", params)?;
 
-    // ## actually, if I understand the docs here https://academy.terra.money/courses/take/cosmwasm-smart-contracts-i/assignments/27056622-building-out-the-rps-game
-    // ## the final state of the game is not saved in cotract
-    // ## it's just verifiable on chain (by including it in response?)
-    let updated_game = match update_game(&mut deps, game) {
-        Ok(game) => game,
-        Err(e) => return Err(e),
-    };
+    Ok(Response::new()
+        .add_attribute("method", "try_addtoallowlist")
+        .add_attribute("addr", checked_addr))
+}
 
-    // get a human friendly string message
-    let result_string = match result {
-        GameResult::Tie {} => "Tie",
-        GameResult::HostWins {} => "Host won",
-        GameResult::OpponentWins {} => "Opponent won",
-    };
+pub fn try_removefromallowlist(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    addr: Addr,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let checked_addr = deps.api.addr_validate(addr.as_ref())?;
 
-    // delete the game from the IndexedMap and create a response with game_result
-    match delete_game(&mut deps, updated_game) {
-        Ok(_) => Ok(Response::new()
-            .add_attribute("method", "try_endgame")
-            .add_attribute("game_result", result_string)),
-        Err(_) => Err(ContractError::CannotFinishGame {}),
+    if !ALLOWLIST.has(deps.storage, &checked_addr) {
+        return Err(ContractError::NotAllowlisted { addr: checked_addr });
     }
+    ALLOWLIST.remove(deps.storage, &checked_addr);
+    let params = vec![attr("addr", &checked_addr)];
+    audit(
+        deps.storage,
+        &env,
+        &info.sender,
+        "remove_from_allowlist",
+        params,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_removefromallowlist")
+        .add_attribute("addr", checked_addr))
 }
 
-pub fn try_updateadmin(
-    deps: DepsMut,
-    info: MessageInfo,
-    admin: Addr,
-) -> Result<Response, AdminError> {
-    // this is using ADMIN controller from cw_controllers
-    // to set a new contract admin
-    // it also checks that only current admin can set the new admin
-    let maybe_admin = maybe_addr(deps.api, Some(admin.to_string()))?;
-    ADMIN.execute_update_admin(deps, info, maybe_admin)
+// helper that fails if allowlist is enabled and addr isn't on it
+pub fn assert_allowlisted(storage: &dyn Storage, addr: &Addr) -> Result<(), ContractError> {
+    let config = CONFIG.may_load(storage)?.unwrap_or_default();
+    if config.allowlist_enabled && !ALLOWLIST.has(storage, addr) {
+        return Err(ContractError::NotAllowlisted { addr: addr.clone() });
+    }
+    Ok(())
 }
 
-pub fn try_addtoblacklist(
+pub fn try_addhook(
     admin: Admin,
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     addr: Addr,
 ) -> Result<Response, HookError> {
-    // this is using HOOKS controller from cw_controllers
-    let checked_addr = deps.api.addr_validate(&addr.to_string())?;
-    BLACKLIST.execute_add_hook(&admin, deps, info, checked_addr)
+    nonpayable(&info).map_err(|err| StdError::generic_err(err.to_string()))?;
+    let checked_addr = deps.api.addr_validate(addr.as_ref())?;
+    // only logged if the controller accepts the change, otherwise the whole tx is reverted anyway
+    let params = vec![attr("addr", &checked_addr)];
+    audit(deps.storage, &env, &info.sender, "add_hook", params)?;
+    HOOKS.execute_add_hook(&admin, deps, info, checked_addr)
 }
 
-pub fn try_removefromblacklist(
+pub fn try_removehook(
     admin: Admin,
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     addr: Addr,
 ) -> Result<Response, HookError> {
-    let checked_addr = deps.api.addr_validate(&addr.to_string())?;
-    BLACKLIST.execute_remove_hook(&admin, deps, info, checked_addr)
+    nonpayable(&info).map_err(|err| StdError::generic_err(err.to_string()))?;
+    let checked_addr = deps.api.addr_validate(addr.as_ref())?;
+    // only logged if the controller accepts the change, otherwise the whole tx is reverted anyway
+    let params = vec![attr("addr", &checked_addr)];
+    audit(deps.storage, &env, &info.sender, "remove_hook", params)?;
+    HOOKS.execute_remove_hook(&admin, deps, info, checked_addr)
+}
+
+// appends a privileged action to the audit log
+fn audit(
+    storage: &mut dyn Storage,
+    env: &Env,
+    actor: &Addr,
+    action: &str,
+    params: Vec<Attribute>,
+) -> StdResult<()> {
+    let id = AUDIT_SEQ.may_load(storage)?.unwrap_or_default() + 1;
+    AUDIT_SEQ.save(storage, &id)?;
+    let entry = AuditEntry {
+        actor: actor.clone(),
+        action: action.to_string(),
+        params,
+        height: env.block.height,
+        time: env.block.time,
+    };
+    audit_log().save(storage, id, &entry)
+}
+
+// helper that builds the message for every registered subscriber
+// a failing subscriber mustn't block games, so hooks only get a reply (and are ignored) on error
+// reply ids from next_reply_id start at 1, so 0 is free for all of them
+pub const HOOK_REPLY_ID: u64 = 0;
+
+fn game_hooks(storage: &dyn Storage, msg: RpsHookMsg) -> StdResult<Vec<SubMsg>> {
+    HOOKS.prepare_hooks(storage, |h| {
+        msg.clone()
+            .into_cosmos_msg(h)
+            .map(|msg| SubMsg::reply_on_error(msg, HOOK_REPLY_ID))
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    if msg.id == HOOK_REPLY_ID {
+        // only sent on error, see game_hooks
+        let error = msg.result.into_result().err().unwrap_or_default();
+        return Ok(Response::new()
+            .add_attribute("method", "reply")
+            .add_attribute("hook_error", error));
+    }
+
+    if let Some((host, bot)) = PENDING_BOT_GAMES.may_load(deps.storage, msg.id)? {
+        PENDING_BOT_GAMES.remove(deps.storage, msg.id);
+        return bot_reply(deps, env, host, bot, msg);
+    }
+
+    let payout = PENDING_PAYOUTS
+        .may_load(deps.storage, msg.id)?
+        .ok_or(ContractError::UnknownReplyId { id: msg.id })?;
+    PENDING_PAYOUTS.remove(deps.storage, msg.id);
+
+    // delivered, nothing else to do
+    if msg.result.is_ok() {
+        return Ok(Response::new().add_attribute("method", "reply"));
+    }
+
+    // the transfer was reverted, so the recipient has to Claim it later
+    let recipient = match payout {
+        Payout::Native { recipient, amount } => {
+            CLAIMS.update(
+                deps.storage,
+                (&recipient, &amount.denom),
+                |claim| -> StdResult<_> { Ok(claim.unwrap_or_default() + amount.amount) },
+            )?;
+            recipient
+        }
+        Payout::Nft { recipient, nft } => {
+            NFT_CLAIMS.save(
+                deps.storage,
+                (&recipient, &nft.contract, &nft.token_id),
+                &nft,
+            )?;
+            recipient
+        }
+    };
+
+    Ok(Response::new()
+        .add_attribute("method", "reply")
+        .add_attribute("payout_failed", recipient))
+}
+
+// bot answered the challenge, its move is played like EndGame would and the host reveals next
+// a bot that fails or doesn't answer with a move calls the game off
+fn bot_reply(
+    deps: DepsMut,
+    env: Env,
+    host: Addr,
+    bot: Addr,
+    msg: Reply,
+) -> Result<Response, ContractError> {
+    let bot_move = parse_reply_execute_data(msg)
+        .ok()
+        .and_then(|res| res.data)
+        .and_then(|data| from_binary::<BotMoveResponse>(&data).ok());
+
+    match bot_move {
+        Some(answer) => {
+            let res = end_game(deps, env, bot.clone(), host, answer.bot_move.into())?;
+            Ok(res.add_attribute("bot", bot))
+        }
+        None => {
+            let game = games().load(deps.storage, (host, bot.clone()))?;
+            let res = cancel_game(deps, game, "bot_reply")?;
+            Ok(res.add_attribute("bot_failed", bot))
+        }
+    }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetGamesByPlayer { player } => to_binary(&query_games(deps, &player)?),
         QueryMsg::GetGamesByHost { host } => to_binary(&query_games_by_host(deps, &host)?),
@@ -252,6 +4036,75 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             to_binary(&query_games_by_opponent(deps, &opponent)?)
         }
         QueryMsg::GetAdmin {} => to_binary(&query_admin(deps)?),
+        QueryMsg::GetPendingAdmin {} => to_binary(&PENDING_ADMIN.may_load(deps.storage)?),
+        QueryMsg::GetHooks {} => to_binary(&query_hooks(deps)?),
+        QueryMsg::GetRoles {} => to_binary(&query_roles(deps)?),
+        QueryMsg::GetTreasury {} => to_binary(&query_treasury(deps)?),
+        QueryMsg::GetRoyale { id } => to_binary(&ROYALES.may_load(deps.storage, id)?),
+        QueryMsg::GetRoyaleMoves { id } => to_binary(&query_royale_moves(deps, id)?),
+        QueryMsg::GetTeamGame { id } => to_binary(&TEAM_GAMES.may_load(deps.storage, id)?),
+        QueryMsg::GetBeacon {} => {
+            to_binary(&CONFIG.may_load(deps.storage)?.unwrap_or_default().beacon)
+        }
+        QueryMsg::GetHouse {} => to_binary(&query_house(deps)?),
+        QueryMsg::GetHouseGame { player } => {
+            to_binary(&HOUSE_GAMES.may_load(deps.storage, &player)?)
+        }
+        QueryMsg::GetConfig {} => to_binary(&CONFIG.may_load(deps.storage)?.unwrap_or_default()),
+        QueryMsg::GetAllowlist { start_after, limit } => {
+            to_binary(&query_allowlist(deps, start_after, limit)?)
+        }
+        QueryMsg::GetStrategy { player } => to_binary(&STRATEGIES.may_load(deps.storage, &player)?),
+        QueryMsg::GetSigningKey { player } => {
+            to_binary(&SIGNING_KEYS.may_load(deps.storage, &player)?)
+        }
+        QueryMsg::GetSignedGameNonce { host, opponent } => to_binary(
+            &SIGNED_GAME_NONCES
+                .may_load(deps.storage, (&host, &opponent))?
+                .unwrap_or_default(),
+        ),
+        QueryMsg::GetRelayNonce { player } => to_binary(
+            &RELAY_NONCES
+                .may_load(deps.storage, &player)?
+                .unwrap_or_default(),
+        ),
+        QueryMsg::GetBots { start_after, limit } => {
+            to_binary(&query_bots(deps, start_after, limit)?)
+        }
+        QueryMsg::GetNftCollections { start_after, limit } => {
+            to_binary(&query_nft_collections(deps, start_after, limit)?)
+        }
+        QueryMsg::GetBlacklist { start_after, limit } => {
+            to_binary(&query_blacklist(deps, env, start_after, limit)?)
+        }
+        QueryMsg::GetBlockList {
+            player,
+            start_after,
+            limit,
+        } => to_binary(&query_block_list(deps, player, start_after, limit)?),
+        QueryMsg::GetBalance { player } => to_binary(&query_balance(deps, &player)?),
+        QueryMsg::GetOperators {
+            player,
+            start_after,
+            limit,
+        } => to_binary(&query_operators(deps, &env, player, start_after, limit)?),
+        QueryMsg::GetClaims { player } => to_binary(&query_claims(deps, &player)?),
+        QueryMsg::GetBetPool { host, opponent } => {
+            to_binary(&query_bet_pool(deps, &host, &opponent)?)
+        }
+        QueryMsg::GetBet {
+            host,
+            opponent,
+            bettor,
+        } => to_binary(&query_bet(deps, &host, &opponent, &bettor)?),
+        QueryMsg::GetAuditLog {
+            start_after,
+            limit,
+            actor,
+        } => to_binary(&query_audit_log(deps, start_after, limit, actor)?),
+        QueryMsg::GetSolvencyReport { cursor, limit } => {
+            to_binary(&solvency_report(deps, &env, cursor, limit)?)
+        }
     }
 }
 
@@ -265,7 +4118,7 @@ fn query_games(deps: Deps, player: &Addr) -> StdResult<GamesListResponse> {
     games.append(&mut games_by_host);
     games.append(&mut games_by_opponent);
 
-    Ok(GamesListResponse { games: games })
+    Ok(GamesListResponse { games })
 }
 
 fn query_games_by_host(deps: Deps, host: &Addr) -> StdResult<GamesListResponse> {
@@ -300,5 +4153,237 @@ fn query_games_by_opponent(deps: Deps, opponent: &Addr) -> StdResult<GamesListRe
 
 // who's the contract admin?
 fn query_admin(deps: Deps) -> StdResult<AdminResponse> {
-    Ok(ADMIN.query_admin(deps)?)
+    ADMIN.query_admin(deps)
+}
+
+// which contracts are notified about games?
+fn query_hooks(deps: Deps) -> StdResult<HooksResponse> {
+    let hooks = HOOKS.query_hooks(deps)?.hooks;
+    Ok(HooksResponse { hooks })
+}
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+fn query_blacklist(
+    deps: Deps,
+    env: Env,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<BlacklistResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|addr| Bound::exclusive(addr.as_ref()));
+
+    let bans = BLACKLIST
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(addr, ban)| BanInfo {
+                addr,
+                active: ban.is_active(&env.block),
+                ban,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(BlacklistResponse { bans })
+}
+
+fn query_block_list(
+    deps: Deps,
+    player: Addr,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<BlockListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|addr| Bound::exclusive(addr.as_ref()));
+
+    let blocked = BLOCKS
+        .prefix(&player)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(BlockListResponse { blocked })
+}
+
+fn query_operators(
+    deps: Deps,
+    env: &Env,
+    player: Addr,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<OperatorsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|addr| Bound::exclusive(addr.as_ref()));
+
+    let operators = OPERATORS
+        .prefix(&player)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(operator, approval)| OperatorInfo {
+                operator,
+                active: !approval.expires.is_expired(&env.block),
+                approval,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(OperatorsResponse { operators })
+}
+
+fn query_roles(deps: Deps) -> StdResult<RolesResponse> {
+    let holders = |role: Role| {
+        ROLES
+            .prefix(role.as_str())
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+    };
+
+    Ok(RolesResponse {
+        admin: ADMIN.query_admin(deps)?.admin,
+        moderators: holders(Role::Moderator)?,
+        fee_managers: holders(Role::FeeManager)?,
+    })
+}
+
+fn query_treasury(deps: Deps) -> StdResult<TreasuryResponse> {
+    let fees = TREASURY
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| coin(amount.u128(), denom)))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(TreasuryResponse { fees })
+}
+
+fn query_royale_moves(deps: Deps, id: u64) -> StdResult<RoyaleMovesResponse> {
+    let moves = ROYALE_MOVES
+        .prefix(id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(RoyaleMovesResponse { moves })
+}
+
+fn query_house(deps: Deps) -> StdResult<HouseResponse> {
+    let bankroll = BANKROLL
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| coin(amount.u128(), denom)))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(HouseResponse {
+        config: CONFIG.may_load(deps.storage)?.unwrap_or_default().house,
+        bankroll,
+    })
+}
+
+fn query_allowlist(
+    deps: Deps,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<AllowlistResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|addr| Bound::exclusive(addr.as_ref()));
+
+    let addrs = ALLOWLIST
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(AllowlistResponse { addrs })
+}
+
+fn query_bots(
+    deps: Deps,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<BotsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|addr| Bound::exclusive(addr.as_ref()));
+
+    let bots = BOTS
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(BotsResponse { bots })
+}
+
+fn query_nft_collections(
+    deps: Deps,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<NftCollectionsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|addr| Bound::exclusive(addr.as_ref()));
+
+    let collections = NFT_COLLECTIONS
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(NftCollectionsResponse { collections })
+}
+
+fn query_balance(deps: Deps, player: &Addr) -> StdResult<BalanceResponse> {
+    let balances = BALANCES
+        .prefix(player)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| coin(amount.u128(), denom)))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(BalanceResponse { balances })
+}
+
+fn query_claims(deps: Deps, player: &Addr) -> StdResult<ClaimsResponse> {
+    let coins = CLAIMS
+        .prefix(player)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| coin(amount.u128(), denom)))
+        .collect::<StdResult<Vec<_>>>()?;
+    let nfts = NFT_CLAIMS
+        .sub_prefix(player)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, nft)| nft))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(ClaimsResponse { coins, nfts })
+}
+
+fn query_bet_pool(deps: Deps, host: &Addr, opponent: &Addr) -> StdResult<BetPoolResponse> {
+    let pool = BET_POOLS.may_load(deps.storage, (host, opponent))?;
+    Ok(BetPoolResponse { pool })
+}
+
+fn query_bet(deps: Deps, host: &Addr, opponent: &Addr, bettor: &Addr) -> StdResult<BetResponse> {
+    let bet = BETS.may_load(deps.storage, (host, opponent, bettor))?;
+    Ok(BetResponse { bet })
+}
+
+fn query_audit_log(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    actor: Option<Addr>,
+) -> StdResult<AuditLogResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|id| Bound::exclusive(id.to_be_bytes().to_vec()));
+
+    let log = audit_log();
+    let entries = match actor {
+        Some(actor) => {
+            log.idx
+                .actor
+                .prefix(actor)
+                .range(deps.storage, start, None, Order::Ascending)
+        }
+        None => log.range(deps.storage, start, None, Order::Ascending),
+    }
+    .take(limit)
+    .map(|item| item.map(|(id, entry)| AuditLogEntry { id, entry }))
+    .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(AuditLogResponse { entries })
 }
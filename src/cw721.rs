@@ -0,0 +1,38 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{to_binary, Addr, Binary, CosmosMsg, StdResult, WasmMsg};
+
+use crate::state::EscrowedNft;
+
+// these mirror the cw721 spec, so that we don't need to pull in the whole cw721 package
+// just for one incoming and one outgoing message
+
+// cw721 contract calls us with this after someone used SendNft { contract: <rps>, .. }
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct Cw721ReceiveMsg {
+    // the previous owner of the token, i.e. the player staking it
+    pub sender: String,
+    pub token_id: String,
+    pub msg: Binary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw721ExecuteMsg {
+    TransferNft { recipient: String, token_id: String },
+}
+
+// helper that builds the message returning (or handing over) an escrowed NFT
+pub fn transfer_nft_msg(nft: &EscrowedNft, recipient: &Addr) -> StdResult<CosmosMsg> {
+    Ok(WasmMsg::Execute {
+        contract_addr: nft.contract.to_string(),
+        msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+            recipient: recipient.to_string(),
+            token_id: nft.token_id.clone(),
+        })?,
+        funds: vec![],
+    }
+    .into())
+}
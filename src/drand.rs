@@ -0,0 +1,56 @@
+use std::convert::TryInto;
+
+use bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
+use bls12_381::{pairing, G1Affine, G2Affine, G2Projective};
+use cosmwasm_std::Timestamp;
+use sha2::{Digest, Sha256};
+
+// verification of drand randomness beacons (https://drand.love), chained scheme as used by
+// the drand mainnet: the group signs sha256(previous_signature || round) with BLS12-381,
+// public key on G1, signatures on G2
+
+const DOMAIN: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+
+pub fn verify_beacon(
+    pubkey: &[u8],
+    round: u64,
+    previous_signature: &[u8],
+    signature: &[u8],
+) -> bool {
+    let pubkey: [u8; 48] = match pubkey.try_into() {
+        Ok(pubkey) => pubkey,
+        Err(_) => return false,
+    };
+    let signature: [u8; 96] = match signature.try_into() {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+    let pubkey = G1Affine::from_compressed(&pubkey);
+    let signature = G2Affine::from_compressed(&signature);
+    if pubkey.is_none().into() || signature.is_none().into() {
+        return false;
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(previous_signature);
+    hasher.update(round.to_be_bytes());
+    let msg = hasher.finalize();
+    let msg_on_curve: G2Affine =
+        <G2Projective as HashToCurve<ExpandMsgXmd<Sha256>>>::hash_to_curve(msg.as_slice(), DOMAIN)
+            .into();
+
+    pairing(&G1Affine::generator(), &signature.unwrap()) == pairing(&pubkey.unwrap(), &msg_on_curve)
+}
+
+// the beacon's random value, same as the "randomness" field drand publishes
+pub fn beacon_randomness(signature: &[u8]) -> [u8; 32] {
+    Sha256::digest(signature).into()
+}
+
+// first round published strictly after `time`, round 1 is published at genesis
+pub fn next_round(genesis: Timestamp, period: u64, time: Timestamp) -> u64 {
+    if time < genesis {
+        return 1;
+    }
+    (time.seconds() - genesis.seconds()) / period + 2
+}
@@ -1,18 +1,55 @@
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Coin, Uint128};
 use cosmwasm_std::StdError;
+use cw_controllers::AdminError;
+use cw_utils::PaymentError;
 use thiserror::Error;
 
+use crate::state::{GameMove, GameStatus, MinusOnePhase, RoyaleStatus, TeamGameStatus};
+
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("{0}")]
+    Admin(#[from] AdminError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
     #[error("Blacklisted address {addr:?}")]
     Blacklisted { addr: Addr },
 
+    #[error("There is no pending admin proposal")]
+    NoPendingAdmin {},
+
+    #[error("Admin proposal has expired")]
+    AdminProposalExpired {},
+
+    #[error("Fee cannot be more than 10000 basis points")]
+    InvalidFee {},
+
+    #[error("Fee cannot be more than {max} basis points")]
+    FeeAboveMax { max: u16 },
+
+    #[error("No treasury address to withdraw fees to")]
+    NoTreasury {},
+
+    #[error("Address {addr:?} is not on the allowlist")]
+    NotAllowlisted { addr: Addr },
+
+    #[error("Address {addr:?} is not blacklisted")]
+    NotBlacklisted { addr: Addr },
+
+    #[error("Player {addr:?} has blocked you")]
+    BlockedByPlayer { addr: Addr },
+
+    #[error("Address {addr:?} is not blocked")]
+    NotBlocked { addr: Addr },
+
     #[error("Game between these two players already exists")]
     DuplicateGame {},
 
@@ -28,6 +65,192 @@ pub enum ContractError {
 
     #[error("Cannot start game against yourself")]
     GameAgainstYourself {},
+
+    #[error("Game is {status:?}")]
+    WrongGameStatus { status: GameStatus },
+
+    #[error("Challenge has to be accepted first")]
+    ChallengeNotAccepted {},
+
+    #[error("Host has to commit their move first")]
+    MoveNotCommitted {},
+
+    #[error("Move is already committed")]
+    MoveAlreadyCommitted {},
+
+    #[error("Revealed move doesn't match the commitment")]
+    InvalidCommitment {},
+
+    #[error("Series length has to be an odd number between 1 and {max}")]
+    InvalidBestOf { max: u8 },
+
+    #[error("Hand has to hold a move for every round of the series")]
+    InvalidHand {},
+
+    #[error("No {game_move:?} left in hand")]
+    MoveExhausted { game_move: GameMove },
+
+    #[error("Not allowed by the game's ruleset")]
+    RulesetMismatch {},
+
+    #[error("Minus one round is in phase {phase:?}")]
+    WrongMinusOnePhase { phase: MinusOnePhase },
+
+    #[error("Can only withdraw one of the two moves shown")]
+    WithdrawnMoveNotShown {},
+
+    #[error("There is no pending counter offer")]
+    NoCounterOffer {},
+
+    #[error("Challenge can be countered at most {max} times")]
+    TooManyCounterOffers { max: usize },
+
+    #[error("Host can have at most {max} open games")]
+    TooManyOpenGames { max: u32 },
+
+    #[error("{addr} is not a registered bot")]
+    NotABot { addr: Addr },
+
+    #[error("Bots only answer challenges sent with CommitChallenge")]
+    BotNeedsCommitment {},
+
+    #[error("{addr} doesn't answer this challenge automatically")]
+    NoAutoAnswer { addr: Addr },
+
+    #[error("No drand beacon is configured")]
+    BeaconNotConfigured {},
+
+    #[error("Invalid strategy")]
+    InvalidStrategy {},
+
+    #[error("No standing strategy set")]
+    NoStrategy {},
+
+    #[error("Invalid signing key")]
+    InvalidSigningKey {},
+
+    #[error("{addr} has no signing key")]
+    NoSigningKey { addr: Addr },
+
+    #[error("Invalid signature from {addr}")]
+    InvalidSignature { addr: Addr },
+
+    #[error("Relayed message is not meant for this contract")]
+    InvalidRelayedMsg {},
+
+    #[error("Wrong nonce, expected {expected}")]
+    WrongNonce { expected: u64 },
+
+    #[error("Not an approved operator of {player}")]
+    NotOperator { player: Addr },
+
+    #[error("Operators can only start and answer games")]
+    NotOperatorAction {},
+
+    #[error("Wager is above the operator limit of {max}")]
+    WagerAboveOperatorLimit { max: Coin },
+
+    #[error("A battle royale needs {min} to {max} players")]
+    InvalidPlayerCount { min: u32, max: u32 },
+
+    #[error("Battle royale is {status:?}")]
+    WrongRoyaleStatus { status: RoyaleStatus },
+
+    #[error("Battle royale is full")]
+    RoyaleFull {},
+
+    #[error("Already in this battle royale")]
+    AlreadyJoined {},
+
+    #[error("Not in this battle royale")]
+    NotInRoyale {},
+
+    #[error("Teams need the same number of members, 1 to {max}, and nobody twice")]
+    InvalidTeams { max: usize },
+
+    #[error("Team game is {status:?}")]
+    WrongTeamGameStatus { status: TeamGameStatus },
+
+    #[error("Not in this team game")]
+    NotInTeam {},
+
+    #[error("House is closed")]
+    HouseClosed {},
+
+    #[error("Invalid house config")]
+    InvalidHouseConfig {},
+
+    #[error("Invalid beacon config")]
+    InvalidBeaconConfig {},
+
+    #[error("Wager is above the house limit of {max}")]
+    WagerAboveHouseLimit { max: Uint128 },
+
+    #[error("You already have an open game against the house")]
+    HouseGameInProgress {},
+
+    #[error("This game is settled by the beacon of round {expected}")]
+    WrongBeaconRound { expected: u64 },
+
+    #[error("Beacon signature is not valid")]
+    InvalidBeacon {},
+
+    #[error("Game has expired")]
+    GameExpired {},
+
+    #[error("Game has not expired yet")]
+    GameNotExpired {},
+
+    #[error("This game requires an NFT stake, use SendNft to accept it")]
+    NftStakeRequired {},
+
+    #[error("This game doesn't accept an NFT stake")]
+    UnexpectedNftStake {},
+
+    #[error("NFTs from collection {collection:?} are not accepted in this game")]
+    NftCollectionNotAllowed { collection: Addr },
+
+    #[error("{addr} is not an approved NFT collection")]
+    UnknownNftCollection { addr: Addr },
+
+    #[error("The host has to accept the opponent's NFT first")]
+    NftNotAccepted {},
+
+    #[error("No NFT waiting to be accepted in this game")]
+    NoNftToReview {},
+
+    #[error("Wager must be a non-zero amount")]
+    InvalidWager {},
+
+    #[error("Timeout is outside the limits set by the admin")]
+    InvalidTimeout {},
+
+    #[error("Insufficient balance, only {available} {denom} available")]
+    InsufficientBalance { denom: String, available: Uint128 },
+
+    #[error("Nothing to claim")]
+    NothingToClaim {},
+
+    #[error("Contract is short of {shortfall} {denom}")]
+    Insolvent { denom: String, shortfall: Uint128 },
+
+    #[error("Unknown reply id {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("Players cannot bet on their own game")]
+    PlayerCannotBet {},
+
+    #[error("Betting on this game is closed")]
+    BettingClosed {},
+
+    #[error("Bets on this game must be placed in {denom}")]
+    WrongBetDenom { denom: String },
+
+    #[error("You already bet on a different outcome of this game")]
+    ConflictingBet {},
+
+    #[error("At most {max} spectators can bet on a game")]
+    TooManyBettors { max: u32 },
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
@@ -0,0 +1,53 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{to_binary, Addr, Binary, Coin, CosmosMsg, StdResult, WasmMsg};
+
+use crate::state::GameResult;
+
+// game lifecycle events sent to every subscriber contract registered with AddHook
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RpsHookMsg {
+    GameStarted {
+        host: Addr,
+        opponent: Addr,
+        wager: Option<Coin>,
+    },
+    GameEnded {
+        host: Addr,
+        opponent: Addr,
+        result: GameResult,
+    },
+    // game was removed without a result (e.g. expired)
+    GameCancelled {
+        host: Addr,
+        opponent: Addr,
+    },
+}
+
+impl RpsHookMsg {
+    // serializes the message so it can be sent to a subscriber
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = RpsHookExecuteMsg::RpsHook(self);
+        to_binary(&msg)
+    }
+
+    // creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+// subscribers need to accept this variant in their ExecuteMsg
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum RpsHookExecuteMsg {
+    RpsHook(RpsHookMsg),
+}
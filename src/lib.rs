@@ -1,6 +1,12 @@
+pub mod commitment;
 pub mod contract;
+pub mod cw721;
+pub mod drand;
 mod error;
+pub mod hooks;
 pub mod msg;
+pub mod signature;
+pub mod solvency;
 pub mod state;
 
 #[cfg(test)]
@@ -1,27 +1,275 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use cosmwasm_std::Addr;
-use crate::state::{GameMove, Game};
+use cosmwasm_std::{Addr, Binary, Coin, Uint128};
+use cw_utils::{Duration, Expiration};
+use crate::cw721::Cw721ReceiveMsg;
+use crate::state::{
+    Approval, AuditEntry, BanRecord, BeaconConfig, Bet, BetPool, EscrowedNft, Game, GameMove,
+    GameResult, GameTerms, HouseConfig, KeyType, Role, RoyaleMove, Strategy, TieBreak,
+    TimeoutLimits,
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub admin: Addr,
+    // only allowlisted addresses can play, off by default
+    #[serde(default)]
+    pub allowlist_enabled: bool,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     // host starts the game by picking opponent and casting first move
+    // the move is public, so games with a stake have to be played as a challenge (ProposeGame)
     StartGame { opponent: Addr, host_move: GameMove },
     // opponent ends game against host by casting a move
     // (there can be only one (host, opponent) game at a time)
+    // in a challenge, opponent matches the wager from their balance when accepting,
+    // winnings are credited to the balance
     EndGame { host: Addr, opponent_move: GameMove },
-    // change contract admin
-    UpdateAdmin { admin: Addr },
-    // manage blacklist of addresses that cannot participate in game
-    AddToBlacklist { addr: Addr },
-    RemoveFromBlacklist { addr: Addr }
+    // two-phase alternative to StartGame: host only proposes the terms, no move yet
+    // opponent accepts (matching the wager) or declines, then each round the host commits,
+    // opponent plays with EndGame and the host reveals
+    ProposeGame { opponent: Addr, terms: GameTerms },
+    AcceptChallenge { host: Addr },
+    // host of an NFT game without opponent_collection accepts the NFT the opponent staked
+    // (needed before anyone can move) or rejects it, which cancels the game and refunds the stakes
+    AcceptNft { opponent: Addr },
+    RejectNft { opponent: Addr },
+    // also works for plain StartGame games the opponent doesn't want to play
+    DeclineChallenge { host: Addr },
+    // opponent asks for different terms, host accepts (replacing the terms) or rejects them,
+    // either way the opponent still has to AcceptChallenge
+    CounterChallenge { host: Addr, terms: GameTerms },
+    AcceptCounter { opponent: Addr },
+    RejectCounter { opponent: Addr },
+    // commitment is sha256(move name + salt), see commitment.rs
+    CommitMove { opponent: Addr, commitment: Binary },
+    RevealMove {
+        opponent: Addr,
+        host_move: GameMove,
+        salt: String,
+    },
+    // challenge a registered bot or a player's standing strategy with a committed move,
+    // the opponent accepts right away (matching the wager from its balance) and answers
+    // once the next drand beacon is submitted, then the host reveals as usual
+    CommitChallenge {
+        opponent: Addr,
+        commitment: Binary,
+        wager: Option<Coin>,
+    },
+    // anyone submits the beacon a CommitChallenge waits for
+    SubmitChallengeBeacon {
+        host: Addr,
+        opponent: Addr,
+        round: u64,
+        previous_signature: Binary,
+        signature: Binary,
+    },
+    // minus one games (accepted like any challenge), sent by host or opponent
+    // both commit to two moves and reveal them, then commit to and reveal the one they withdraw
+    CommitMinusOne {
+        host: Addr,
+        opponent: Addr,
+        commitment: Binary,
+    },
+    RevealMinusOne {
+        host: Addr,
+        opponent: Addr,
+        moves: (GameMove, GameMove),
+        salt: String,
+    },
+    CommitWithdrawal {
+        host: Addr,
+        opponent: Addr,
+        commitment: Binary,
+    },
+    RevealWithdrawal {
+        host: Addr,
+        opponent: Addr,
+        withdrawn: GameMove,
+        salt: String,
+    },
+    // add sent funds to the internal balance
+    Deposit {},
+    // send funds from the internal balance back to the player
+    Withdraw { amount: Coin },
+    // retry all payouts to the sender that failed when their game was resolved
+    Claim {},
+    // sender's own block list, blocked addresses can't start games against the sender
+    BlockPlayer { addr: Addr },
+    UnblockPlayer { addr: Addr },
+    // admin transfer in two steps, the proposed admin has to accept it
+    ProposeAdmin {
+        admin: Addr,
+        expires: Option<Expiration>,
+    },
+    AcceptAdmin {},
+    CancelAdminProposal {},
+    // leave the contract without admin on purpose, cannot be undone
+    RenounceAdmin {},
+    // admin grants and revokes moderator / fee manager roles
+    GrantRole { role: Role, addr: Addr },
+    RevokeRole { role: Role, addr: Addr },
+    // moderator removes an abusive game, refunding all stakes
+    CancelGame { host: Addr, opponent: Addr },
+    // fee manager sets the house fee (up to the admin's max_fee_bps) and withdraws
+    // collected fees to the admin's treasury address
+    UpdateFee { fee_bps: u16 },
+    WithdrawFees { amount: Coin },
+    // manage blacklist of addresses that cannot participate in game (moderator)
+    // a ban without expiry is permanent, adding an already banned address replaces the ban
+    AddToBlacklist {
+        addr: Addr,
+        reason: Option<String>,
+        expires: Option<Expiration>,
+    },
+    RemoveFromBlacklist { addr: Addr },
+    // admin settings, fields that are not set are left unchanged
+    // zero bond / max_open_games removes the bond / the cap
+    // lowering max_fee_bps below the current fee lowers the fee as well
+    UpdateConfig {
+        allowlist_enabled: Option<bool>,
+        game_bond: Option<Coin>,
+        max_open_games: Option<u32>,
+        max_fee_bps: Option<u16>,
+        treasury: Option<Addr>,
+        timeout_limits: Option<TimeoutLimits>,
+    },
+    // manage allowlist of addresses that can play when allowlist is enabled
+    AddToAllowlist { addr: Addr },
+    RemoveFromAllowlist { addr: Addr },
+    // player answers CommitChallenge challenges automatically while offline, as long as the
+    // wager is within max_wager, the balance covers it (funds sent are deposited) and fewer
+    // than max_answers challenges were taken on
+    SetStrategy {
+        strategy: Strategy,
+        max_wager: Option<Coin>,
+        max_answers: u64,
+    },
+    ClearStrategy {},
+    // players register the key they sign moves with off-chain
+    SetSigningKey { key_type: KeyType, pubkey: Binary },
+    RemoveSigningKey {},
+    // anyone (either player or a relayer) plays a whole game in one go with both signed moves,
+    // wagers are taken from the players' balances
+    PlaySigned {
+        host: Addr,
+        opponent: Addr,
+        wager: Option<Coin>,
+        expires: Expiration,
+        host_move: SignedMove,
+        opponent_move: SignedMove,
+    },
+    // relayer submits any player action on the player's behalf, see signature.rs
+    // payload is a signed RelayedMsg, the player is the account of the secp256k1 pubkey
+    ExecuteSigned {
+        payload: Binary,
+        signature: Binary,
+        pubkey: Binary,
+    },
+    // player lets an operator (e.g. a hot wallet) start and answer games for them,
+    // approving the same operator again replaces the limits
+    ApproveOperator {
+        operator: Addr,
+        max_wager: Option<Coin>,
+        expires: Option<Expiration>,
+    },
+    RevokeOperator { operator: Addr },
+    // operator runs ProposeGame, EndGame, AcceptChallenge, DeclineChallenge,
+    // CommitMove, RevealMove or the minus one messages as the player,
+    // wagers come from the player's balance
+    ExecuteAs { player: Addr, msg: Box<ExecuteMsg> },
+    // manage subscriber contracts receiving RpsHookMsg
+    AddHook { addr: Addr },
+    RemoveHook { addr: Addr },
+    // manage bot contracts that answer CommitChallenge on their own with a BotChallengeMsg
+    RegisterBot { addr: Addr },
+    RemoveBot { addr: Addr },
+    // manage cw721 collections NFTs can be staked from
+    AddNftCollection { addr: Addr },
+    RemoveNftCollection { addr: Addr },
+    // anyone can clean up a game that wasn't finished in time,
+    // escrowed NFTs are returned to their owners
+    ExpireGame { host: Addr, opponent: Addr },
+    // cw721 SendNft hook, the msg is NftReceiveMsg
+    ReceiveNft(Cw721ReceiveMsg),
+    // spectators bet (exactly one coin) on the outcome of an open game
+    // betting closes when the opponent moves, players can't bet on their own game
+    PlaceBet { host: Addr, opponent: Addr, outcome: GameResult },
+    // battle royale for 3 to 64 players, whoever creates it joins right away
+    // the host starts it (or it starts once full), then every round the survivors commit
+    // and reveal, before the deadline of each phase, see README
+    CreateRoyale {
+        entry_fee: Option<Coin>,
+        max_players: u32,
+        phase_timeout: Option<Duration>,
+    },
+    JoinRoyale { id: u64 },
+    StartRoyale { id: u64 },
+    CommitRoyaleMove { id: u64, commitment: Binary },
+    RevealRoyaleMove { id: u64, game_move: GameMove, salt: String },
+    // anyone moves the royale on once the deadline passed, players who didn't act are out
+    AdvanceRoyale { id: u64 },
+    // team game, whoever creates it has to be in the host team
+    // every member commits a vote (staking the wager) and reveals it, each team plays its majority
+    CreateTeamGame {
+        host_team: Vec<Addr>,
+        opponent_team: Vec<Addr>,
+        wager: Option<Coin>,
+        tie_break: TieBreak,
+        phase_timeout: Option<Duration>,
+    },
+    CommitTeamVote { id: u64, commitment: Binary },
+    RevealTeamVote { id: u64, game_move: GameMove, salt: String },
+    // anyone moves the game on once the deadline passed, votes not cast in time don't count
+    AdvanceTeamGame { id: u64 },
+    // drand chain house games, bots and standing strategies are played with, set by the admin
+    UpdateBeacon { beacon: BeaconConfig },
+    // house mode: admin sets it up and funds (with all coins sent) or withdraws the bankroll
+    UpdateHouse { house: HouseConfig },
+    FundBankroll {},
+    WithdrawBankroll { amount: Coin, recipient: Addr },
+    // player plays against the house, wager is taken from the balance
+    // the house move comes from the first drand round published after this block
+    PlayHouse { player_move: GameMove, wager: Coin },
+    // anyone settles a house game with the drand beacon of its round
+    SubmitBeacon {
+        player: Addr,
+        round: u64,
+        previous_signature: Binary,
+        signature: Binary,
+    },
+    // anyone refunds a house game nobody settled in time
+    ExpireHouseGame { player: Addr },
+}
+
+// a revealed move and the player's signature of the SignedMovePayload committing to it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SignedMove {
+    pub game_move: GameMove,
+    pub salt: String,
+    pub signature: Binary,
+}
+
+// sent along with the staked NFT in Cw721ReceiveMsg.msg
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NftReceiveMsg {
+    // host stakes the NFT and proposes the game like ProposeGame
+    // opponent then has to stake an NFT from opponent_collection (or any NFT if not set)
+    ProposeGame {
+        opponent: Addr,
+        terms: GameTerms,
+        opponent_collection: Option<Addr>,
+    },
+    // opponent stakes the NFT and accepts like AcceptChallenge, winner gets both NFTs
+    AcceptChallenge { host: Addr },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -32,6 +280,69 @@ pub enum QueryMsg {
     // get all games where player is either host or opponent
     GetGamesByPlayer { player: Addr },
     GetAdmin {},
+    GetPendingAdmin {},
+    GetHooks {},
+    GetConfig {},
+    GetRoles {},
+    GetTreasury {},
+    GetRoyale { id: u64 },
+    // current round's commitments and revealed moves
+    GetRoyaleMoves { id: u64 },
+    GetTeamGame { id: u64 },
+    GetBeacon {},
+    GetHouse {},
+    GetHouseGame { player: Addr },
+    GetAllowlist {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    GetStrategy { player: Addr },
+    GetSigningKey { player: Addr },
+    // nonce the next signed game of these players has to be signed with
+    GetSignedGameNonce { host: Addr, opponent: Addr },
+    // nonce the next message relayed for the player has to be signed with
+    GetRelayNonce { player: Addr },
+    GetBots {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    GetNftCollections {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    // lists bans including lapsed ones, ordered by address
+    GetBlacklist {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    GetBalance { player: Addr },
+    // addresses blocked by given player, ordered by address
+    GetBlockList {
+        player: Addr,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    // lists a player's operators including lapsed ones, ordered by address
+    GetOperators {
+        player: Addr,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    GetClaims { player: Addr },
+    GetBetPool { host: Addr, opponent: Addr },
+    GetBet { host: Addr, opponent: Addr, bettor: Addr },
+    // compares everything the contract owes to its actual bank balance
+    // sums up at most `limit` entries per call, pass `next` back until the full report comes
+    GetSolvencyReport {
+        cursor: Option<SolvencyCursor>,
+        limit: Option<u32>,
+    },
+    // privileged actions, oldest first, optionally only those by given actor
+    GetAuditLog {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        actor: Option<Addr>,
+    },
 }
 
 // We define a custom struct for each query response
@@ -39,3 +350,172 @@ pub enum QueryMsg {
 pub struct GamesListResponse {
     pub games: Vec<Game>,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BetPoolResponse {
+    // none until the first bet is placed
+    pub pool: Option<BetPool>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BetResponse {
+    pub bet: Option<Bet>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BalanceResponse {
+    // one coin per denom, zero balances are left out
+    pub balances: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimsResponse {
+    pub coins: Vec<Coin>,
+    pub nfts: Vec<EscrowedNft>,
+}
+
+// empty denoms until the last page, which has next: None
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SolvencyReportResponse {
+    pub denoms: Vec<DenomSolvency>,
+    // true if every denom can be paid back in full
+    pub solvent: bool,
+    pub next: Option<SolvencyCursor>,
+}
+
+// where the report continues and what it summed up so far
+// the totals are whatever the caller passes back and aren't checked, a report is only
+// as good as the cursors it was fed, so don't take one built by someone else as proof
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SolvencyCursor {
+    pub source: SolvencySource,
+    // raw storage key of the last entry summed up in source
+    pub start_after: Option<Binary>,
+    pub totals: Vec<DenomSolvency>,
+}
+
+// the maps the report walks, in this order
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SolvencySource {
+    Games,
+    Royales,
+    TeamGames,
+    BetPools,
+    Claims,
+    Balances,
+    Treasury,
+    Bankroll,
+    HouseGames,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct DenomSolvency {
+    pub denom: String,
+    // wagers escrowed in open games
+    pub game_escrow: Uint128,
+    // spectator bets on open games
+    pub bet_pools: Uint128,
+    // failed payouts waiting to be claimed
+    pub claims: Uint128,
+    // internal player balances
+    pub balances: Uint128,
+    // collected house fees
+    pub treasury: Uint128,
+    // house mode bankroll, including wagers and winnings at stake in open house games
+    pub house: Uint128,
+    // sum of all of the above
+    pub liabilities: Uint128,
+    // what the contract actually holds
+    pub contract_balance: Uint128,
+    // how much is missing to pay everyone back, zero if solvent
+    pub shortfall: Uint128,
+}
+
+// same shape as cw_controllers' hooks response, which isn't exported
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HooksResponse {
+    pub hooks: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BlacklistResponse {
+    pub bans: Vec<BanInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BanInfo {
+    pub addr: Addr,
+    pub ban: BanRecord,
+    // false once the ban lapsed
+    pub active: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BlockListResponse {
+    pub blocked: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OperatorsResponse {
+    pub operators: Vec<OperatorInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OperatorInfo {
+    pub operator: Addr,
+    pub approval: Approval,
+    // false once the approval expired
+    pub active: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AllowlistResponse {
+    pub addrs: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BotsResponse {
+    pub bots: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NftCollectionsResponse {
+    pub collections: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RolesResponse {
+    pub admin: Option<String>,
+    pub moderators: Vec<Addr>,
+    pub fee_managers: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TreasuryResponse {
+    pub fees: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoyaleMovesResponse {
+    pub moves: Vec<(Addr, RoyaleMove)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HouseResponse {
+    pub config: Option<HouseConfig>,
+    // free part of the bankroll, not at stake in open games
+    pub bankroll: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuditLogResponse {
+    pub entries: Vec<AuditLogEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuditLogEntry {
+    // pass the last id as start_after to get the next page
+    pub id: u64,
+    pub entry: AuditEntry,
+}
@@ -0,0 +1,81 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use bech32::{ToBase32, Variant};
+use cosmwasm_std::{to_vec, Addr, Api, Binary, Coin, StdResult};
+use cw_utils::Expiration;
+use ripemd160::Ripemd160;
+use sha2::{Digest, Sha256};
+
+use crate::msg::ExecuteMsg;
+use crate::state::{KeyType, SigningKey};
+
+// moves signed off-chain, so that a whole game can be played in one transaction
+// players sign the JSON serialization of SignedMovePayload in their key's native way:
+// secp256k1 over sha256 of the bytes (as usual for ECDSA), ed25519 over the bytes themselves
+
+pub const SIGNED_MOVE_DOMAIN: &str = "rps_signed_move";
+
+// every field is part of the signature, so a signed move can't be replayed in another
+// contract, another game, a later game of the same players or with another wager.
+// both players sign the same payload with both move commitments (see commitment.rs),
+// so neither signs after seeing the other's move, and PlaySigned carries the reveals
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SignedMovePayload {
+    pub domain: String,
+    pub contract: Addr,
+    pub host: Addr,
+    pub opponent: Addr,
+    // see GetSignedGameNonce
+    pub nonce: u64,
+    pub wager: Option<Coin>,
+    pub host_commitment: Binary,
+    pub opponent_commitment: Binary,
+    // the signatures can't be played after this
+    pub expires: Expiration,
+}
+
+impl SignedMovePayload {
+    pub fn to_bytes(&self) -> StdResult<Vec<u8>> {
+        to_vec(self)
+    }
+}
+
+// meta-transactions: a relayer submits ExecuteSigned with the JSON serialization of
+// a RelayedMsg the player signed with their secp256k1 account key (over sha256 of the bytes)
+
+pub const RELAYED_MSG_DOMAIN: &str = "rps_execute_signed";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RelayedMsg {
+    pub domain: String,
+    pub chain_id: String,
+    pub contract: Addr,
+    // see GetRelayNonce
+    pub nonce: u64,
+    pub msg: ExecuteMsg,
+}
+
+// account address of a compressed secp256k1 public key, the way the cosmos sdk derives it
+pub fn derive_address(prefix: &str, pubkey: &[u8]) -> Option<String> {
+    if pubkey.len() != 33 {
+        return None;
+    }
+    let hash = Ripemd160::digest(&Sha256::digest(pubkey));
+    bech32::encode(prefix, hash.to_base32(), Variant::Bech32).ok()
+}
+
+pub fn verify_signature(
+    api: &dyn Api,
+    key: &SigningKey,
+    message: &[u8],
+    signature: &Binary,
+) -> bool {
+    let verified = match key.key_type {
+        KeyType::Secp256k1 => {
+            api.secp256k1_verify(&Sha256::digest(message), signature, &key.pubkey)
+        }
+        KeyType::Ed25519 => api.ed25519_verify(message, signature, &key.pubkey),
+    };
+    verified.unwrap_or(false)
+}
@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{Binary, Deps, Env, Order, StdResult, Uint128};
+use cw_storage_plus::{Bound, PrimaryKey};
+
+use crate::error::ContractError;
+use crate::msg::{DenomSolvency, SolvencyCursor, SolvencyReportResponse, SolvencySource};
+use crate::state::{
+    games, BALANCES, BANKROLL, BET_POOLS, CLAIMS, HOUSE_GAMES, ROYALES, TEAM_GAMES, TREASURY,
+};
+
+// entries summed up per page
+const DEFAULT_LIMIT: u32 = 100;
+const MAX_LIMIT: u32 = 1000;
+
+// sums up everything the contract owes, per denom, and compares it to what it actually holds
+// every open game, balance and claim is an entry, so the report goes page by page:
+// each page sums up at most `limit` entries and hands the totals on in the cursor.
+// those totals are taken from the caller as they are, only what a page sums up
+// itself, the contract balance, liabilities and shortfall are computed here
+pub fn solvency_report(
+    deps: Deps,
+    env: &Env,
+    cursor: Option<SolvencyCursor>,
+    limit: Option<u32>,
+) -> StdResult<SolvencyReportResponse> {
+    let mut budget = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize;
+    let (mut source, mut start_after, totals) = match cursor {
+        Some(cursor) => (cursor.source, cursor.start_after, cursor.totals),
+        None => (SolvencySource::Games, None, vec![]),
+    };
+    let mut denoms: BTreeMap<String, DenomSolvency> = totals
+        .into_iter()
+        .map(|denom| (denom.denom.clone(), denom))
+        .collect();
+
+    let next = loop {
+        let start = start_after.take().map(|key| Bound::exclusive(key.to_vec()));
+        let storage = deps.storage;
+        let resume = match source {
+            // wagers and bonds escrowed in open games, opponent's part only once they matched it
+            SolvencySource::Games => walk(
+                games().range(storage, start, None, Order::Ascending),
+                &mut budget,
+                |(host, opponent)| (host, opponent).joined_key(),
+                |_, game| {
+                    if let Some(wager) = &game.wager {
+                        let mut escrowed = wager.amount;
+                        if game.opponent_joined() {
+                            escrowed += wager.amount;
+                        }
+                        entry(&mut denoms, &wager.denom).game_escrow += escrowed;
+                    }
+                    if let Some(bond) = &game.bond {
+                        entry(&mut denoms, &bond.denom).game_escrow += bond.amount;
+                    }
+                },
+            )?,
+            // entry fees of battle royales
+            SolvencySource::Royales => walk(
+                ROYALES.range(storage, start, None, Order::Ascending),
+                &mut budget,
+                |id| id.joined_key(),
+                |_, royale| {
+                    if let Some(fee) = &royale.entry_fee {
+                        entry(&mut denoms, &fee.denom).game_escrow += royale.pot;
+                    }
+                },
+            )?,
+            // stakes of team games
+            SolvencySource::TeamGames => walk(
+                TEAM_GAMES.range(storage, start, None, Order::Ascending),
+                &mut budget,
+                |id| id.joined_key(),
+                |_, team_game| {
+                    if let Some(wager) = &team_game.wager {
+                        entry(&mut denoms, &wager.denom).game_escrow += team_game.pot;
+                    }
+                },
+            )?,
+            SolvencySource::BetPools => walk(
+                BET_POOLS.range(storage, start, None, Order::Ascending),
+                &mut budget,
+                |(host, opponent)| (host, opponent).joined_key(),
+                |_, pool| entry(&mut denoms, &pool.denom).bet_pools += pool.total(),
+            )?,
+            SolvencySource::Claims => walk(
+                CLAIMS.range(storage, start, None, Order::Ascending),
+                &mut budget,
+                |(player, denom)| (player, denom.as_str()).joined_key(),
+                |(_, denom), amount| entry(&mut denoms, denom).claims += *amount,
+            )?,
+            SolvencySource::Balances => walk(
+                BALANCES.range(storage, start, None, Order::Ascending),
+                &mut budget,
+                |(player, denom)| (player, denom.as_str()).joined_key(),
+                |(_, denom), amount| entry(&mut denoms, denom).balances += *amount,
+            )?,
+            SolvencySource::Treasury => walk(
+                TREASURY.range(storage, start, None, Order::Ascending),
+                &mut budget,
+                |denom| denom.as_str().joined_key(),
+                |denom, amount| entry(&mut denoms, denom).treasury += *amount,
+            )?,
+            SolvencySource::Bankroll => walk(
+                BANKROLL.range(storage, start, None, Order::Ascending),
+                &mut budget,
+                |denom| denom.as_str().joined_key(),
+                |denom, amount| entry(&mut denoms, denom).house += *amount,
+            )?,
+            // house games hold the player's wager and what the house put up against it
+            SolvencySource::HouseGames => walk(
+                HOUSE_GAMES.range(storage, start, None, Order::Ascending),
+                &mut budget,
+                |player| player.joined_key(),
+                |_, game| {
+                    entry(&mut denoms, &game.wager.denom).house +=
+                        game.wager.amount + game.reserved;
+                },
+            )?,
+        };
+
+        // out of budget, the next page picks up from here
+        match (resume, next_source(source)) {
+            (Some(start_after), _) => break Some((source, start_after)),
+            (None, Some(next)) => source = next,
+            (None, None) => break None,
+        }
+    };
+    if let Some((source, start_after)) = next {
+        return Ok(SolvencyReportResponse {
+            denoms: vec![],
+            solvent: false,
+            next: Some(SolvencyCursor {
+                source,
+                start_after,
+                totals: denoms.into_values().collect(),
+            }),
+        });
+    }
+
+    for held in deps
+        .querier
+        .query_all_balances(env.contract.address.to_string())?
+    {
+        entry(&mut denoms, &held.denom).contract_balance = held.amount;
+    }
+
+    let denoms: Vec<DenomSolvency> = denoms
+        .into_values()
+        .map(|mut denom| {
+            denom.liabilities = denom.game_escrow
+                + denom.bet_pools
+                + denom.claims
+                + denom.balances
+                + denom.treasury
+                + denom.house;
+            denom.shortfall = denom.liabilities.saturating_sub(denom.contract_balance);
+            denom
+        })
+        .collect();
+    let solvent = denoms
+        .iter()
+        .all(|denom| denom.shortfall == Uint128::zero());
+
+    Ok(SolvencyReportResponse {
+        denoms,
+        solvent,
+        next: None,
+    })
+}
+
+// sums up entries until the budget runs out. if there are more left, returns where
+// to continue: after the raw key of the last one summed up, or from the start
+fn walk<K, T>(
+    items: impl Iterator<Item = StdResult<(K, T)>>,
+    budget: &mut usize,
+    raw_key: impl Fn(&K) -> Vec<u8>,
+    mut add: impl FnMut(&K, &T),
+) -> StdResult<Option<Option<Binary>>> {
+    let mut last = None;
+    for item in items {
+        if *budget == 0 {
+            return Ok(Some(last.map(|key| Binary::from(raw_key(&key)))));
+        }
+        let (key, value) = item?;
+        add(&key, &value);
+        *budget -= 1;
+        last = Some(key);
+    }
+    Ok(None)
+}
+
+fn next_source(source: SolvencySource) -> Option<SolvencySource> {
+    match source {
+        SolvencySource::Games => Some(SolvencySource::Royales),
+        SolvencySource::Royales => Some(SolvencySource::TeamGames),
+        SolvencySource::TeamGames => Some(SolvencySource::BetPools),
+        SolvencySource::BetPools => Some(SolvencySource::Claims),
+        SolvencySource::Claims => Some(SolvencySource::Balances),
+        SolvencySource::Balances => Some(SolvencySource::Treasury),
+        SolvencySource::Treasury => Some(SolvencySource::Bankroll),
+        SolvencySource::Bankroll => Some(SolvencySource::HouseGames),
+        SolvencySource::HouseGames => None,
+    }
+}
+
+fn entry<'a>(
+    denoms: &'a mut BTreeMap<String, DenomSolvency>,
+    denom: &str,
+) -> &'a mut DenomSolvency {
+    denoms
+        .entry(denom.to_string())
+        .or_insert_with(|| DenomSolvency {
+            denom: denom.to_string(),
+            ..DenomSolvency::default()
+        })
+}
+
+// invariant check, fails on the first denom the contract couldn't pay back in full
+pub fn assert_solvent(deps: Deps, env: &Env) -> Result<(), ContractError> {
+    let mut report = solvency_report(deps, env, None, Some(MAX_LIMIT))?;
+    while let Some(cursor) = report.next {
+        report = solvency_report(deps, env, Some(cursor), Some(MAX_LIMIT))?;
+    }
+    match report.denoms.into_iter().find(|d| !d.shortfall.is_zero()) {
+        Some(denom) => Err(ContractError::Insolvent {
+            denom: denom.denom,
+            shortfall: denom.shortfall,
+        }),
+        None => Ok(()),
+    }
+}
@@ -1,13 +1,70 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Addr;
-use cw_storage_plus::{Index, IndexList, IndexedMap, Item, MultiIndex, UniqueIndex};
+use cosmwasm_std::{Addr, Attribute, Binary, BlockInfo, Coin, Empty, Timestamp, Uint128};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex, UniqueIndex};
 
 use cw_controllers::{Admin, Hooks};
+use cw_utils::{Duration, Expiration, DAY};
+
+use rps_bot::BotMove;
 
 pub const ADMIN: Admin = Admin::new("admin");
-pub const BLACKLIST: Hooks = Hooks::new("blacklist");
+
+// admin transfer waiting for the new admin to accept it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingAdmin {
+    pub new_admin: Addr,
+    // proposal can't be accepted after this, never expires if not set
+    pub expires: Option<Expiration>,
+}
+
+pub const PENDING_ADMIN: Item<PendingAdmin> = Item::new("pending_admin");
+
+// why, by whom and until when an address is banned from playing
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BanRecord {
+    pub reason: Option<String>,
+    pub banned_by: Addr,
+    pub banned_at: Timestamp,
+    // ban lapses on its own after this, permanent if not set
+    pub expires: Option<Expiration>,
+}
+
+impl BanRecord {
+    pub fn is_active(&self, block: &BlockInfo) -> bool {
+        match &self.expires {
+            Some(expires) => !expires.is_expired(block),
+            None => true,
+        }
+    }
+}
+
+// addresses that cannot participate in games
+pub const BLACKLIST: Map<&Addr, BanRecord> = Map::new("bans");
+// the blacklist as cw_controllers::Hooks stored it before ban records, moved over by migrate
+pub const LEGACY_BLACKLIST: Item<Vec<Addr>> = Item::new("blacklist");
+// subscriber contracts notified about game lifecycle, see hooks.rs
+pub const HOOKS: Hooks = Hooks::new("hooks");
+// admin approved bot contracts that answer CommitChallenge on their own, see the rps-bot package
+pub const BOTS: Map<&Addr, Empty> = Map::new("bots");
+// admin approved cw721 collections, only these can send us NFTs
+// (ReceiveNft names the player, so a fake collection could otherwise play in anyone's name)
+pub const NFT_COLLECTIONS: Map<&Addr, Empty> = Map::new("nft_collections");
+
+// used when the host doesn't specify how long the opponent has to answer
+pub const DEFAULT_GAME_TIMEOUT: Duration = DAY;
+// longest series that can be agreed on
+pub const MAX_BEST_OF: u8 = 9;
+// how many times the opponent can counter a challenge
+pub const MAX_COUNTER_OFFERS: usize = 3;
+// how many players a battle royale can have
+pub const MIN_ROYALE_PLAYERS: u32 = 3;
+pub const MAX_ROYALE_PLAYERS: u32 = 64;
+// how many spectators can bet on one game, every bet is settled when the game ends
+pub const MAX_BETTORS: u32 = 50;
+// largest team in a team game
+pub const MAX_TEAM_SIZE: usize = 16;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
@@ -16,6 +73,245 @@ pub struct State {
 
 pub const STATE: Item<State> = Item::new("state");
 
+// admin managed settings
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Config {
+    // only allowlisted addresses can start or end games (e.g. private deployments)
+    pub allowlist_enabled: bool,
+    // house fee taken from the pot of won games, in basis points (set by fee manager)
+    pub fee_bps: u16,
+    // highest fee_bps the fee manager can set, DEFAULT_MAX_FEE_BPS if not set
+    pub max_fee_bps: Option<u16>,
+    // collected fees can only be withdrawn to this address, not at all if not set
+    pub treasury: Option<Addr>,
+    // refundable anti-spam bond the host puts up for every game, none if not set
+    pub game_bond: Option<Coin>,
+    // how many games a host can have open at once, unlimited if not set
+    pub max_open_games: Option<u32>,
+    // house mode settings, players can't play against the house if not set
+    pub house: Option<HouseConfig>,
+    // drand chain house games, bots and standing strategies draw their moves from,
+    // none of them can be played if not set
+    pub beacon: Option<BeaconConfig>,
+    // shortest and longest timeout a game or phase can have, DEFAULT_TIMEOUT_LIMITS if not set
+    pub timeout_limits: Option<TimeoutLimits>,
+}
+
+// bounds for timeouts in blocks (Duration::Height) and in seconds (Duration::Time)
+// too short a timeout lets a player forfeit the other one before they could act
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+pub struct TimeoutLimits {
+    pub min_blocks: u64,
+    pub max_blocks: u64,
+    pub min_seconds: u64,
+    pub max_seconds: u64,
+}
+
+impl TimeoutLimits {
+    pub fn allows(&self, timeout: &Duration) -> bool {
+        match *timeout {
+            Duration::Height(blocks) => (self.min_blocks..=self.max_blocks).contains(&blocks),
+            Duration::Time(seconds) => (self.min_seconds..=self.max_seconds).contains(&seconds),
+        }
+    }
+}
+
+// drand chain the beacons are verified against: group public key (compressed G1),
+// genesis time and round period in seconds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BeaconConfig {
+    pub pubkey: Binary,
+    pub genesis: Timestamp,
+    pub period: u64,
+}
+
+// house mode, a player plays against the contract itself
+// the house move comes from a drand beacon published after the player moved
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HouseConfig {
+    // closed house takes no new games, but open ones can still be settled
+    pub open: bool,
+    // taken from the player's winnings, in basis points
+    pub edge_bps: u16,
+    // largest wager as a share of the bankroll in that denom, in basis points
+    pub max_wager_bps: u16,
+}
+
+impl Config {
+    pub fn max_fee_bps(&self) -> u16 {
+        self.max_fee_bps.unwrap_or(DEFAULT_MAX_FEE_BPS)
+    }
+
+    pub fn timeout_limits(&self) -> TimeoutLimits {
+        self.timeout_limits.unwrap_or(DEFAULT_TIMEOUT_LIMITS)
+    }
+}
+
+// 10% of the pot, until the admin sets another cap
+pub const DEFAULT_MAX_FEE_BPS: u16 = 1000;
+// 10 blocks or a minute up to a week (~6s blocks), until the admin sets other limits
+pub const DEFAULT_TIMEOUT_LIMITS: TimeoutLimits = TimeoutLimits {
+    min_blocks: 10,
+    max_blocks: 100_800,
+    min_seconds: 60,
+    max_seconds: 7 * 24 * 60 * 60,
+};
+
+pub const CONFIG: Item<Config> = Item::new("config");
+// privileged roles besides the admin, admin can do everything any role can
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    // manages blacklist and cancels abusive games
+    Moderator,
+    // sets house fee and withdraws collected fees
+    FeeManager,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Role::Moderator => "moderator",
+            Role::FeeManager => "fee_manager",
+        }
+    }
+}
+
+// (role, addr) -> granted
+pub const ROLES: Map<(&str, &Addr), Empty> = Map::new("roles");
+// denom -> collected house fees
+pub const TREASURY: Map<&str, Uint128> = Map::new("treasury");
+
+// battle royale, N players commit and reveal moves round by round until one survives
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RoyaleStatus {
+    // players are joining
+    Open,
+    // survivors commit their moves for the round
+    Commit,
+    // survivors reveal their committed moves
+    Reveal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Royale {
+    pub host: Addr,
+    // taken from every player's balance when joining, the pot goes to the last survivor
+    pub entry_fee: Option<Coin>,
+    pub pot: Uint128,
+    pub max_players: u32,
+    // everyone who joined, then whoever is still in the game
+    pub players: Vec<Addr>,
+    pub status: RoyaleStatus,
+    pub round: u32,
+    // time to start the royale, then to commit and to reveal in every round
+    pub phase_timeout: Duration,
+    pub deadline: Expiration,
+}
+
+// a survivor's move in the current round
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoyaleMove {
+    pub commitment: Binary,
+    pub game_move: Option<GameMove>,
+}
+
+pub const ROYALE_SEQ: Item<u64> = Item::new("royale_seq");
+pub const ROYALES: Map<u64, Royale> = Map::new("royales");
+// (royale id, player) -> move in the current round, cleared between rounds
+pub const ROYALE_MOVES: Map<(u64, &Addr), RoyaleMove> = Map::new("royale_moves");
+
+// team game, every team plays the move most of its members voted for
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TeamGameStatus {
+    // members commit their votes
+    Commit,
+    // members reveal their committed votes
+    Reveal,
+}
+
+// what a team plays if its votes are split evenly
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TieBreak {
+    // the first member in the team list who voted for one of the tied moves decides
+    Captain {},
+    // the team plays no move and loses
+    Forfeit {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TeamVote {
+    pub member: Addr,
+    pub commitment: Binary,
+    pub game_move: Option<GameMove>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TeamGame {
+    // the first host team member created the game
+    pub host_team: Vec<Addr>,
+    pub opponent_team: Vec<Addr>,
+    // taken from every member's balance when they vote
+    pub wager: Option<Coin>,
+    pub pot: Uint128,
+    pub tie_break: TieBreak,
+    pub votes: Vec<TeamVote>,
+    pub status: TeamGameStatus,
+    // time to commit, then to reveal
+    pub phase_timeout: Duration,
+    pub deadline: Expiration,
+}
+
+pub const TEAM_GAME_SEQ: Item<u64> = Item::new("team_game_seq");
+pub const TEAM_GAMES: Map<u64, TeamGame> = Map::new("team_games");
+
+// denom -> admin funded bankroll the house pays winners from, minus what's at stake in open games
+pub const BANKROLL: Map<&str, Uint128> = Map::new("bankroll");
+
+// drand round a pending game waits for, along with the public key of the config at the time,
+// so changing the config can't swap the key the beacon is checked against
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BeaconRound {
+    pub pubkey: Binary,
+    pub round: u64,
+}
+
+// a player's game against the house, waiting for the beacon of its round
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HouseGame {
+    pub player: Addr,
+    pub player_move: GameMove,
+    pub wager: Coin,
+    // what the house pays on top of the wager if the player wins, taken from the bankroll
+    pub reserved: Uint128,
+    pub beacon: BeaconRound,
+    // if nobody settled the game by then, anyone can expire it and the stakes go back
+    pub expires: Expiration,
+}
+
+// player -> open game against the house, one at a time
+pub const HOUSE_GAMES: Map<&Addr, HouseGame> = Map::new("house_games");
+
+// (player, blocked addr) -> blocked, player won't get challenges from blocked addr
+pub const BLOCKS: Map<(&Addr, &Addr), Empty> = Map::new("blocks");
+
+// operator a player allows to start and answer games for them, similar to cw721 approvals
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Approval {
+    // largest wager the operator can put up for the player, unlimited if not set
+    pub max_wager: Option<Coin>,
+    pub expires: Expiration,
+}
+
+// (player, operator) -> approval
+pub const OPERATORS: Map<(&Addr, &Addr), Approval> = Map::new("operators");
+
+// addresses allowed to play when allowlist is enabled
+pub const ALLOWLIST: Map<&Addr, Empty> = Map::new("allowlist");
+
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
 pub enum GameMove {
     Rock {},
@@ -23,6 +319,77 @@ pub enum GameMove {
     Scissors {},
 }
 
+impl GameMove {
+    // name used in move commitments, see commitment.rs
+    pub fn as_str(&self) -> &str {
+        match self {
+            GameMove::Rock {} => "rock",
+            GameMove::Paper {} => "paper",
+            GameMove::Scissors {} => "scissors",
+        }
+    }
+}
+
+impl From<BotMove> for GameMove {
+    fn from(bot_move: BotMove) -> Self {
+        match bot_move {
+            BotMove::Rock {} => GameMove::Rock {},
+            BotMove::Paper {} => GameMove::Paper {},
+            BotMove::Scissors {} => GameMove::Scissors {},
+        }
+    }
+}
+
+// how a player's CommitChallenge challenges are answered while they are offline
+// the strategy is public state, so every answer is drawn from the drand beacon of a round
+// published after the host committed, nobody can tell it in advance
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    // moves picked with given weights by hashing the beacon randomness together with
+    // the host and the number of the answered game, every move needs a non-zero weight
+    // (uneven weights still give the host an edge, max_wager and max_answers bound it)
+    Weighted { rock: u32, paper: u32, scissors: u32 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StandingStrategy {
+    pub strategy: Strategy,
+    // largest wager answered, challenges with no wager only if not set
+    pub max_wager: Option<Coin>,
+    // how many challenges the strategy takes on in total, bounds what it can lose
+    pub max_answers: u64,
+    // challenges taken on so far
+    pub accepted: u64,
+    // how many challenges were answered so far
+    pub answered: u64,
+}
+
+// player -> standing strategy
+pub const STRATEGIES: Map<&Addr, StandingStrategy> = Map::new("strategies");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyType {
+    Secp256k1,
+    Ed25519,
+}
+
+// key a player signs moves with off-chain, see signature.rs
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SigningKey {
+    pub key_type: KeyType,
+    // compressed or uncompressed secp256k1 key, 32 bytes ed25519 key
+    pub pubkey: Binary,
+}
+
+// player -> signing key
+pub const SIGNING_KEYS: Map<&Addr, SigningKey> = Map::new("signing_keys");
+// (host, opponent) -> signed games played so far, signed payloads include it against replays
+pub const SIGNED_GAME_NONCES: Map<(&Addr, &Addr), u64> = Map::new("signed_game_nonces");
+// player -> messages relayed for them so far, relayed messages include it against replays
+pub const RELAY_NONCES: Map<&Addr, u64> = Map::new("relay_nonces");
+
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
 pub enum GameResult {
     HostWins {},
@@ -30,17 +397,361 @@ pub enum GameResult {
     Tie {},
 }
 
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GameStatus {
+    // waiting for the opponent to join
+    // (plain StartGame already has the host move locked in, EndGame joins and plays at once)
+    Proposed,
+    // opponent accepted the terms and matched the wager, host commits, then opponent moves
+    Accepted,
+    // opponent moved, host has to reveal the committed move
+    AwaitingReveal,
+    Finished,
+}
+
+// rules the moves are evaluated by
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Ruleset {
+    Classic {},
+    // every player starts with the same hand and each move played is used up
+    LimitedHand { hand: Hand },
+    // both players show two moves, then each withdraws one, see MinusOne
+    MinusOne {},
+}
+
+impl Ruleset {
+    // hand each player starts with, if the ruleset limits moves
+    pub fn starting_hand(&self) -> Option<Hand> {
+        match self {
+            Ruleset::LimitedHand { hand } => Some(hand.clone()),
+            _ => None,
+        }
+    }
+
+    // first round of a minus one game
+    pub fn minus_one(&self) -> Option<MinusOne> {
+        match self {
+            Ruleset::MinusOne {} => Some(MinusOne::default()),
+            _ => None,
+        }
+    }
+}
+
+// how many times each move can still be played
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default, JsonSchema)]
+pub struct Hand {
+    pub rock: u8,
+    pub paper: u8,
+    pub scissors: u8,
+}
+
+impl Hand {
+    pub fn remaining(&self, game_move: &GameMove) -> u8 {
+        match game_move {
+            GameMove::Rock {} => self.rock,
+            GameMove::Paper {} => self.paper,
+            GameMove::Scissors {} => self.scissors,
+        }
+    }
+
+    // uses up one of the move, false if there is none left
+    pub fn play(&mut self, game_move: &GameMove) -> bool {
+        let left = match game_move {
+            GameMove::Rock {} => &mut self.rock,
+            GameMove::Paper {} => &mut self.paper,
+            GameMove::Scissors {} => &mut self.scissors,
+        };
+        match left.checked_sub(1) {
+            Some(fewer) => {
+                *left = fewer;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn size(&self) -> u32 {
+        self.rock as u32 + self.paper as u32 + self.scissors as u32
+    }
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Ruleset::Classic {}
+    }
+}
+
+// what the opponent agrees to when accepting a challenge
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct GameTerms {
+    pub wager: Option<Coin>,
+    #[serde(default)]
+    pub ruleset: Ruleset,
+    // odd number of won rounds needed is best_of / 2 + 1, tied rounds are replayed
+    pub best_of: u8,
+    // time for each step of the game, DEFAULT_GAME_TIMEOUT if not set
+    pub timeout: Option<Duration>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CounterOfferStatus {
+    Pending,
+    Accepted,
+    Rejected,
+}
+
+// terms the opponent asked for instead of the proposed ones
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct CounterOffer {
+    pub terms: GameTerms,
+    pub status: CounterOfferStatus,
+}
+
+// phases of a minus one round, both players act in each of them
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MinusOnePhase {
+    #[default]
+    CommitMoves,
+    RevealMoves,
+    CommitWithdrawal,
+    RevealWithdrawal,
+}
+
+impl MinusOnePhase {
+    pub fn as_str(&self) -> &str {
+        match self {
+            MinusOnePhase::CommitMoves => "commit_moves",
+            MinusOnePhase::RevealMoves => "reveal_moves",
+            MinusOnePhase::CommitWithdrawal => "commit_withdrawal",
+            MinusOnePhase::RevealWithdrawal => "reveal_withdrawal",
+        }
+    }
+}
+
+// one player's side of a minus one round
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default, JsonSchema)]
+pub struct MinusOneHand {
+    // to both moves first (see commitment.rs), then to the withdrawn one
+    pub commitment: Option<Binary>,
+    pub moves: Option<(GameMove, GameMove)>,
+    pub withdrawn: Option<GameMove>,
+}
+
+// current round of a minus one game, the moves left after withdrawing are played as usual
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default, JsonSchema)]
+pub struct MinusOne {
+    pub phase: MinusOnePhase,
+    pub host: MinusOneHand,
+    pub opponent: MinusOneHand,
+}
+
+impl MinusOne {
+    pub fn hand_mut(&mut self, host: bool) -> &mut MinusOneHand {
+        if host {
+            &mut self.host
+        } else {
+            &mut self.opponent
+        }
+    }
+
+    // whether the player is done with the current phase
+    pub fn acted(&self, host: bool) -> bool {
+        let hand = if host { &self.host } else { &self.opponent };
+        match self.phase {
+            MinusOnePhase::CommitMoves | MinusOnePhase::CommitWithdrawal => {
+                hand.commitment.is_some()
+            }
+            MinusOnePhase::RevealMoves => hand.moves.is_some(),
+            MinusOnePhase::RevealWithdrawal => hand.withdrawn.is_some(),
+        }
+    }
+
+    // whoever acted wins when the phase times out on the other player
+    pub fn forfeit(&self) -> Option<GameResult> {
+        match (self.acted(true), self.acted(false)) {
+            (true, false) => Some(GameResult::HostWins {}),
+            (false, true) => Some(GameResult::OpponentWins {}),
+            _ => None,
+        }
+    }
+}
+
+// rounds won so far in a series
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default, JsonSchema)]
+pub struct Score {
+    pub host: u8,
+    pub opponent: u8,
+}
+
+// cw721 token held by the contract while the game is running
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct EscrowedNft {
+    pub contract: Addr,
+    pub token_id: String,
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
 pub struct Game {
     pub host: Addr,
     pub opponent: Addr,
-    pub host_move: GameMove,
+    pub status: GameStatus,
+    // known from the start in plain StartGame, only after reveal in a challenge
+    pub host_move: Option<GameMove>,
+    // sha256 commitment to the host move of the current round, see commitment.rs
+    pub host_commitment: Option<Binary>,
     // optional, not known at the start of the game
     pub opponent_move: Option<GameMove>,
     // optional, not known before opponent_move is known
     pub result: Option<GameResult>,
+    // optional, coins both players put in from their internal balance
+    pub wager: Option<Coin>,
+    // optional, NFT staked by host ("pink slip" game)
+    pub host_nft: Option<EscrowedNft>,
+    // optional, collection the opponent has to stake from, any NFT is accepted if not set
+    // only relevant when host_nft is set
+    pub opponent_collection: Option<Addr>,
+    // optional, NFT staked by opponent
+    pub opponent_nft: Option<EscrowedNft>,
+    // without opponent_collection, the host has to accept the opponent's NFT before playing
+    #[serde(default)]
+    pub opponent_nft_accepted: bool,
+    // host's anti-spam bond, returned once the game is over
+    // (forfeited to the treasury if the host doesn't reveal in time)
+    pub bond: Option<Coin>,
+    pub ruleset: Ruleset,
+    pub best_of: u8,
+    pub score: Score,
+    // moves left in a limited hand game
+    #[serde(default)]
+    pub host_hand: Option<Hand>,
+    #[serde(default)]
+    pub opponent_hand: Option<Hand>,
+    // current round of a minus one game
+    #[serde(default)]
+    pub minus_one: Option<MinusOne>,
+    // set while a CommitChallenge waits for the beacon the opponent's answer is drawn with
+    #[serde(default)]
+    pub beacon: Option<BeaconRound>,
+    // time each step gets, expires is moved forward by this after every step
+    pub timeout: Duration,
+    // negotiation history of a challenge, oldest first, only the last one can be pending
+    pub counter_offers: Vec<CounterOffer>,
+    // after this, the game can't be finished anymore and anyone can expire it,
+    // which releases the escrowed NFTs back to their owners
+    // (or makes the host forfeit, if the game is waiting for their reveal)
+    pub expires: Expiration,
 }
 
+// a game as it was stored before challenges, series and stakes, rewritten into a Game by migrate
+// (unknown fields are denied, so games stored since don't pass as one)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct LegacyGame {
+    pub host: Addr,
+    pub opponent: Addr,
+    pub host_move: GameMove,
+    pub opponent_move: Option<GameMove>,
+    pub result: Option<GameResult>,
+}
+
+// same storage as games(), by raw (host, opponent) key
+pub const LEGACY_GAMES: Map<&[u8], LegacyGame> = Map::new("games");
+
+impl Game {
+    // opponent's part of the wager is escrowed once they joined
+    pub fn opponent_joined(&self) -> bool {
+        self.status != GameStatus::Proposed
+    }
+
+    // opponent staked an NFT of their choice that the host hasn't accepted yet
+    pub fn awaiting_nft_review(&self) -> bool {
+        self.opponent_nft.is_some() && self.opponent_collection.is_none() && !self.opponent_nft_accepted
+    }
+
+    pub fn pending_counter_offer(&mut self) -> Option<&mut CounterOffer> {
+        self.counter_offers
+            .last_mut()
+            .filter(|offer| offer.status == CounterOfferStatus::Pending)
+    }
+}
+
+// (player, denom) -> amount the player can wager or withdraw
+pub const BALANCES: Map<(&Addr, &str), Uint128> = Map::new("balances");
+
+// PAYOUTS
+
+// something the contract owes a player once a game is resolved
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub enum Payout {
+    Native { recipient: Addr, amount: Coin },
+    Nft { recipient: Addr, nft: EscrowedNft },
+}
+
+// payouts are sent as submessages, so that a recipient that can't receive them
+// doesn't block the game, failed payouts are moved to the claims below
+pub const PAYOUT_SEQ: Item<u64> = Item::new("payout_seq");
+// reply id -> payout in flight
+pub const PENDING_PAYOUTS: Map<u64, Payout> = Map::new("pending_payouts");
+// reply id -> (host, bot) of a challenge the bot is answering, ids come from PAYOUT_SEQ too
+pub const PENDING_BOT_GAMES: Map<u64, (Addr, Addr)> = Map::new("pending_bot_games");
+// (recipient, denom) -> amount to Claim
+pub const CLAIMS: Map<(&Addr, &str), Uint128> = Map::new("claims");
+// (recipient, collection, token_id) -> NFT to Claim
+pub const NFT_CLAIMS: Map<(&Addr, &Addr, &str), EscrowedNft> = Map::new("nft_claims");
+
+// SPECTATOR BETS
+
+// parimutuel pool attached to a (host, opponent) game
+// all bets in one pool are in the same denom, set by the first bet
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct BetPool {
+    pub denom: String,
+    pub host_wins: Uint128,
+    pub opponent_wins: Uint128,
+    pub tie: Uint128,
+    #[serde(default)]
+    pub bettors: u32,
+}
+
+impl BetPool {
+    pub fn total(&self) -> Uint128 {
+        self.host_wins + self.opponent_wins + self.tie
+    }
+
+    // total staked on given outcome
+    pub fn on_outcome(&self, outcome: &GameResult) -> Uint128 {
+        match outcome {
+            GameResult::HostWins {} => self.host_wins,
+            GameResult::OpponentWins {} => self.opponent_wins,
+            GameResult::Tie {} => self.tie,
+        }
+    }
+
+    pub fn add(&mut self, outcome: &GameResult, amount: Uint128) {
+        match outcome {
+            GameResult::HostWins {} => self.host_wins += amount,
+            GameResult::OpponentWins {} => self.opponent_wins += amount,
+            GameResult::Tie {} => self.tie += amount,
+        }
+    }
+}
+
+// one bettor can only back one outcome per game
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, JsonSchema)]
+pub struct Bet {
+    pub outcome: GameResult,
+    pub amount: Uint128,
+}
+
+// (host, opponent) -> pool
+pub const BET_POOLS: Map<(&Addr, &Addr), BetPool> = Map::new("bet_pools");
+// (host, opponent, bettor) -> bet
+pub const BETS: Map<(&Addr, &Addr, &Addr), Bet> = Map::new("bets");
+
 // INDEXED MAP
 
 pub struct GameIndexes<'a> {
@@ -83,3 +794,36 @@ pub fn games<'a>() -> IndexedMap<'a, (Addr, Addr), Game, GameIndexes<'a>> {
     };
     IndexedMap::new("games", indexes)
 }
+
+// AUDIT LOG
+
+// one privileged (admin / moderator / fee manager) action, entries are never changed or removed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuditEntry {
+    pub actor: Addr,
+    pub action: String,
+    pub params: Vec<Attribute>,
+    pub height: u64,
+    pub time: Timestamp,
+}
+
+pub const AUDIT_SEQ: Item<u64> = Item::new("audit_seq");
+
+pub struct AuditIndexes<'a> {
+    pub actor: MultiIndex<'a, Addr, AuditEntry, u64>,
+}
+
+impl<'a> IndexList<AuditEntry> for AuditIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<AuditEntry>> + '_> {
+        let v: Vec<&dyn Index<AuditEntry>> = vec![&self.actor];
+        Box::new(v.into_iter())
+    }
+}
+
+// entry id -> entry, ids are increasing so the log is ordered by time
+pub fn audit_log<'a>() -> IndexedMap<'a, u64, AuditEntry, AuditIndexes<'a>> {
+    let indexes = AuditIndexes {
+        actor: MultiIndex::new(|e| e.actor.clone(), "audit_log", "audit_log__actor"),
+    };
+    IndexedMap::new("audit_log", indexes)
+}
@@ -1,15 +1,43 @@
+use crate::commitment::{move_commitment, moves_commitment};
 use crate::contract::*;
 
-use cw_controllers::AdminResponse;
+use cw_controllers::{AdminError, AdminResponse, HookError};
 
+use crate::cw721::{Cw721ExecuteMsg, Cw721ReceiveMsg};
+use crate::drand::{beacon_randomness, next_round, verify_beacon};
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, GamesListResponse, InstantiateMsg, QueryMsg};
-use crate::state::{games, Game, GameMove, BLACKLIST};
+use crate::hooks::RpsHookMsg;
+use crate::msg::{
+    AllowlistResponse, AuditLogResponse, BalanceResponse, BetPoolResponse, BlacklistResponse,
+    BlockListResponse, BotsResponse, ClaimsResponse, ExecuteMsg, GamesListResponse, HooksResponse,
+    HouseResponse, InstantiateMsg, MigrateMsg, NftCollectionsResponse, NftReceiveMsg,
+    OperatorsResponse, QueryMsg, RolesResponse, SignedMove, SolvencyReportResponse,
+    TreasuryResponse,
+};
+use crate::signature::{
+    derive_address, RelayedMsg, SignedMovePayload, RELAYED_MSG_DOMAIN, SIGNED_MOVE_DOMAIN,
+};
+use crate::solvency::assert_solvent;
+use crate::state::{
+    games, BeaconConfig, CounterOffer, CounterOfferStatus, EscrowedNft, Game, GameMove, GameResult,
+    GameStatus, GameTerms, Hand, HouseConfig, HouseGame, KeyType, MinusOnePhase, PendingAdmin,
+    Role, Royale, RoyaleStatus, Ruleset, Score, StandingStrategy, Strategy, TeamGame,
+    TeamGameStatus, TieBreak, TimeoutLimits, DEFAULT_GAME_TIMEOUT, LEGACY_BLACKLIST, LEGACY_GAMES,
+    MAX_BETTORS, MAX_TEAM_SIZE, PENDING_PAYOUTS,
+};
 
 use cosmwasm_std::testing::{
-    mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info,
+    mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info, MOCK_CONTRACT_ADDR,
+};
+use cosmwasm_std::{
+    attr, coin, coins, from_binary, to_binary, Addr, BankMsg, Binary, Coin, ContractResult,
+    CosmosMsg, Deps, DepsMut, Env, Order, Reply, Storage, SubMsg, SubMsgExecutionResponse,
+    Timestamp, Uint128, WasmMsg,
 };
-use cosmwasm_std::{coins, from_binary, Addr, Order};
+use cw_storage_plus::PrimaryKey;
+use cw_utils::{Duration, Expiration, PaymentError};
+use k256::ecdsa::signature::Signer;
+use rps_bot::{BotChallengeMsg, BotMove, BotMoveResponse};
 
 #[test]
 fn proper_initialization() {
@@ -18,6 +46,7 @@ fn proper_initialization() {
     let info = mock_info("creator", &coins(1000, "earth"));
     let msg = InstantiateMsg {
         admin: Addr::unchecked("bobby"),
+        allowlist_enabled: false,
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -135,6 +164,14 @@ fn end_game_with_tie_move() {
     assert_eq!(res.attributes[1].value, "Tie");
 }
 
+fn query_blacklist(deps: Deps) -> BlacklistResponse {
+    let msg = QueryMsg::GetBlacklist {
+        start_after: None,
+        limit: None,
+    };
+    from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+}
+
 #[test]
 fn add_to_and_remove_from_blacklist() {
     let mut deps = mock_dependencies();
@@ -143,24 +180,28 @@ fn add_to_and_remove_from_blacklist() {
     let info = mock_info("creator", &coins(1000, "earth"));
     let msg = InstantiateMsg {
         admin: Addr::unchecked("bobby"),
+        allowlist_enabled: false,
     };
     let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
 
     // add "black" to blacklist
-    let info = mock_info("bobby", &coins(2, "token"));
+    let info = mock_info("bobby", &[]);
     let msg = ExecuteMsg::AddToBlacklist {
         addr: Addr::unchecked("black"),
+        reason: None,
+        expires: None,
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(res.messages.len(), 0); // TODO: is this a correct test?
 
     // check "black" is on blacklist
-    let blacklist = BLACKLIST.query_hooks(deps.as_ref()).unwrap();
-    assert_eq!(blacklist.hooks, ["black"]);
+    let blacklist = query_blacklist(deps.as_ref());
+    assert_eq!(blacklist.bans.len(), 1);
+    assert_eq!(blacklist.bans[0].addr, "black");
 
     // remove "black" from blacklist
-    let info = mock_info("bobby", &coins(2, "token"));
+    let info = mock_info("bobby", &[]);
     let msg = ExecuteMsg::RemoveFromBlacklist {
         addr: Addr::unchecked("black"),
     };
@@ -168,8 +209,107 @@ fn add_to_and_remove_from_blacklist() {
     assert_eq!(res.messages.len(), 0); // TODO: is this a correct test?
 
     // check blacklist is empty now
-    let blacklist = BLACKLIST.query_hooks(deps.as_ref()).unwrap();
-    assert_eq!(blacklist.hooks, [] as [&str; 0]);
+    let blacklist = query_blacklist(deps.as_ref());
+    assert_eq!(blacklist.bans, []);
+}
+
+#[test]
+fn migrate_moves_legacy_blacklist() {
+    let mut deps = mock_dependencies();
+
+    let info = mock_info("creator", &[]);
+    let msg = InstantiateMsg {
+        admin: Addr::unchecked("bobby"),
+        allowlist_enabled: false,
+    };
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    // as the old cw_controllers::Hooks blacklist stored it
+    LEGACY_BLACKLIST
+        .save(&mut deps.storage, &vec![Addr::unchecked("black")])
+        .unwrap();
+
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    assert!(res.attributes.contains(&attr("migrated_bans", "1")));
+    assert_eq!(LEGACY_BLACKLIST.may_load(&deps.storage).unwrap(), None);
+
+    // the ban is permanent and still keeps "black" from playing
+    let blacklist = query_blacklist(deps.as_ref());
+    assert_eq!(blacklist.bans.len(), 1);
+    assert_eq!(blacklist.bans[0].addr, "black");
+    assert!(blacklist.bans[0].active);
+    assert_eq!(blacklist.bans[0].ban.expires, None);
+    assert_eq!(blacklist.bans[0].ban.banned_by, "bobby");
+    let info = mock_info("black", &[]);
+    let msg = ExecuteMsg::StartGame {
+        opponent: Addr::unchecked("opponent"),
+        host_move: GameMove::Rock {},
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::Blacklisted {
+            addr: Addr::unchecked("black")
+        }
+    );
+}
+
+#[test]
+fn migrate_rewrites_legacy_games() {
+    let mut deps = mock_dependencies();
+    let msg = InstantiateMsg {
+        admin: Addr::unchecked("bobby"),
+        allowlist_enabled: false,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+    let msg = ExecuteMsg::StartGame {
+        opponent: Addr::unchecked("opponent"),
+        host_move: GameMove::Paper {},
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg).unwrap();
+    // as the first version stored it, with the same index entries
+    let pk = (&Addr::unchecked("host"), &Addr::unchecked("opponent")).joined_key();
+    let key = LEGACY_GAMES.key(&pk);
+    deps.storage.set(
+        &key,
+        br#"{"host":"host","opponent":"opponent","host_move":{"Paper":{}},"opponent_move":null,"result":null}"#,
+    );
+    assert!(games()
+        .load(
+            &deps.storage,
+            (Addr::unchecked("host"), Addr::unchecked("opponent"))
+        )
+        .is_err());
+
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    assert!(res.attributes.contains(&attr("migrated_games", "1")));
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::GetGamesByHost {
+            host: Addr::unchecked("host"),
+        },
+    )
+    .unwrap();
+    let list: GamesListResponse = from_binary(&res).unwrap();
+    assert_eq!(list.games.len(), 1);
+    let game = &list.games[0];
+    assert_eq!(game.status, GameStatus::Proposed);
+    assert_eq!(game.host_move, Some(GameMove::Paper {}));
+    assert_eq!(game.expires, DEFAULT_GAME_TIMEOUT.after(&mock_env().block));
+
+    // and can be played as usual
+    let msg = ExecuteMsg::EndGame {
+        host: Addr::unchecked("host"),
+        opponent_move: GameMove::Scissors {},
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("opponent", &[]), msg).unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("game_result", "Opponent won")));
+    // running it again finds nothing left to convert
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    assert!(res.attributes.contains(&attr("migrated_games", "0")));
 }
 
 #[test]
@@ -180,14 +320,17 @@ fn blacklisted_addr_cannot_start_game() {
     let info = mock_info("creator", &coins(1000, "earth"));
     let msg = InstantiateMsg {
         admin: Addr::unchecked("creator"),
+        allowlist_enabled: false,
     };
     let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
 
     // add "black" to blacklist
-    let info = mock_info("creator", &coins(2, "token"));
+    let info = mock_info("creator", &[]);
     let msg = ExecuteMsg::AddToBlacklist {
         addr: Addr::unchecked("black"),
+        reason: None,
+        expires: None,
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(res.messages.len(), 0); // TODO: is this a correct test?
@@ -208,7 +351,7 @@ fn blacklisted_addr_cannot_start_game() {
     );
 
     // remove "black" from blacklist
-    let info = mock_info("creator", &coins(2, "token"));
+    let info = mock_info("creator", &[]);
     let msg = ExecuteMsg::RemoveFromBlacklist {
         addr: Addr::unchecked("black"),
     };
@@ -233,14 +376,17 @@ fn cannot_start_game_against_blacklisted() {
     let info = mock_info("creator", &coins(1000, "earth"));
     let msg = InstantiateMsg {
         admin: Addr::unchecked("creator"),
+        allowlist_enabled: false,
     };
     let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
 
     // add "black" to blacklist
-    let info = mock_info("creator", &coins(2, "token"));
+    let info = mock_info("creator", &[]);
     let msg = ExecuteMsg::AddToBlacklist {
         addr: Addr::unchecked("black"),
+        reason: None,
+        expires: None,
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(res.messages.len(), 0); // TODO: is this a correct test?
@@ -261,7 +407,7 @@ fn cannot_start_game_against_blacklisted() {
     );
 
     // remove "black" from blacklist
-    let info = mock_info("creator", &coins(2, "token"));
+    let info = mock_info("creator", &[]);
     let msg = ExecuteMsg::RemoveFromBlacklist {
         addr: Addr::unchecked("black"),
     };
@@ -289,6 +435,7 @@ fn blacklisted_cannot_end_game() {
     let info = mock_info("creator", &coins(1000, "earth"));
     let msg = InstantiateMsg {
         admin: Addr::unchecked("creator"),
+        allowlist_enabled: false,
     };
     let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
@@ -303,9 +450,11 @@ fn blacklisted_cannot_end_game() {
     assert_eq!(res.messages.len(), 0);
 
     // add "black" to blacklist
-    let info = mock_info("creator", &coins(2, "token"));
+    let info = mock_info("creator", &[]);
     let msg = ExecuteMsg::AddToBlacklist {
         addr: Addr::unchecked("black"),
+        reason: None,
+        expires: None,
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(res.messages.len(), 0); // TODO: is this a correct test?
@@ -338,6 +487,7 @@ fn cannot_end_game_against_blacklisted() {
     let info = mock_info("creator", &coins(1000, "earth"));
     let msg = InstantiateMsg {
         admin: Addr::unchecked("admin"),
+        allowlist_enabled: false,
     };
     let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
@@ -352,9 +502,11 @@ fn cannot_end_game_against_blacklisted() {
     assert_eq!(res.messages.len(), 0);
 
     // add "creator" to blacklist by "admin"
-    let info = mock_info("admin", &coins(2, "token"));
+    let info = mock_info("admin", &[]);
     let msg = ExecuteMsg::AddToBlacklist {
         addr: Addr::unchecked("creator"),
+        reason: None,
+        expires: None,
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(res.messages.len(), 0); // TODO: is this a correct test?
@@ -383,19 +535,99 @@ fn update_admin() {
     let info = mock_info("creator", &coins(1000, "earth"));
     let msg = InstantiateMsg {
         admin: Addr::unchecked("bobby"),
+        allowlist_enabled: false,
     };
 
     // we can just call .unwrap() to assert this was a success
     let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
 
-    // now only "bobby" can update admin
-    let info = mock_info("bobby", &coins(2, "token"));
-    let msg = ExecuteMsg::UpdateAdmin {
+    // now only "bobby" can propose a new admin
+    let msg = ExecuteMsg::ProposeAdmin {
         admin: Addr::unchecked("adrianne"),
+        expires: Some(Expiration::AtHeight(mock_env().block.height + 10)),
     };
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    assert_eq!(res.messages.len(), 0); // TODO: is this a correct test?
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("adrianne", &[]),
+        msg.clone(),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::Admin(AdminError::NotAdmin {})
+    );
+    let res = execute(deps.as_mut(), mock_env(), mock_info("bobby", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    // nothing changes until the proposed admin accepts
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::GetAdmin {}).unwrap();
+    let admin: AdminResponse = from_binary(&res).unwrap();
+    assert_eq!(admin.admin, Some("bobby".to_string()));
+
+    // nobody else can accept
+    let info = mock_info("mallory", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AcceptAdmin {});
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::Unauthorized {});
+
+    // proposal can't be accepted after it expired
+    let mut env = mock_env();
+    env.block.height += 11;
+    let info = mock_info("adrianne", &[]);
+    let err = execute(deps.as_mut(), env, info.clone(), ExecuteMsg::AcceptAdmin {});
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::AdminProposalExpired {});
+
+    execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AcceptAdmin {}).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::GetAdmin {}).unwrap();
+    let admin: AdminResponse = from_binary(&res).unwrap();
+    assert_eq!(admin.admin, Some("adrianne".to_string()));
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::GetPendingAdmin {}).unwrap();
+    assert_eq!(from_binary::<Option<PendingAdmin>>(&res).unwrap(), None);
+}
+
+#[test]
+fn cancel_proposal_and_renounce_admin() {
+    let mut deps = mock_dependencies();
+
+    let info = mock_info("creator", &[]);
+    let msg = InstantiateMsg {
+        admin: Addr::unchecked("bobby"),
+        allowlist_enabled: false,
+    };
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // typo in the address can be taken back
+    let msg = ExecuteMsg::ProposeAdmin {
+        admin: Addr::unchecked("adriane"),
+        expires: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("bobby", &[]), msg).unwrap();
+    let msg = ExecuteMsg::CancelAdminProposal {};
+    execute(deps.as_mut(), mock_env(), mock_info("bobby", &[]), msg).unwrap();
+    let info = mock_info("adriane", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AcceptAdmin {});
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::NoPendingAdmin {});
+
+    // renouncing leaves the contract without admin, pending proposal is dropped as well
+    let msg = ExecuteMsg::ProposeAdmin {
+        admin: Addr::unchecked("adrianne"),
+        expires: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("bobby", &[]), msg).unwrap();
+    let msg = ExecuteMsg::RenounceAdmin {};
+    execute(deps.as_mut(), mock_env(), mock_info("bobby", &[]), msg).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::GetAdmin {}).unwrap();
+    let admin: AdminResponse = from_binary(&res).unwrap();
+    assert_eq!(admin.admin, None);
+
+    let info = mock_info("adrianne", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AcceptAdmin {});
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::NoPendingAdmin {});
 }
 
 #[test]
@@ -445,9 +677,27 @@ fn query_games() {
         [Game {
             host: Addr::unchecked("tony"),
             opponent: Addr::unchecked("oprah"),
-            host_move: GameMove::Scissors {},
+            status: GameStatus::Proposed,
+            host_move: Some(GameMove::Scissors {}),
+            host_commitment: None,
             opponent_move: None,
-            result: None
+            result: None,
+            wager: None,
+            host_nft: None,
+            opponent_collection: None,
+            opponent_nft: None,
+            opponent_nft_accepted: false,
+            bond: None,
+            ruleset: Ruleset::Classic {},
+            best_of: 1,
+            score: Score::default(),
+            host_hand: None,
+            opponent_hand: None,
+            minus_one: None,
+            beacon: None,
+            timeout: DEFAULT_GAME_TIMEOUT,
+            counter_offers: vec![],
+            expires: DEFAULT_GAME_TIMEOUT.after(&mock_env().block),
         }]
     )
 }
@@ -460,6 +710,7 @@ fn query_admin() {
     let info = mock_info("creator", &coins(1000, "earth"));
     let msg = InstantiateMsg {
         admin: Addr::unchecked("bobby"),
+        allowlist_enabled: false,
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -486,27 +737,81 @@ fn game_indexedmap() {
     let game11 = Game {
         host: host1.clone(),
         opponent: opponent1.clone(),
-        host_move: GameMove::Rock {},
+        status: GameStatus::Proposed,
+        host_move: Some(GameMove::Rock {}),
+        host_commitment: None,
         opponent_move: None,
         result: None,
+        wager: None,
+        host_nft: None,
+        opponent_collection: None,
+        opponent_nft: None,
+        opponent_nft_accepted: false,
+        bond: None,
+        ruleset: Ruleset::Classic {},
+        best_of: 1,
+        score: Score::default(),
+        host_hand: None,
+        opponent_hand: None,
+        minus_one: None,
+        beacon: None,
+        timeout: DEFAULT_GAME_TIMEOUT,
+        counter_offers: vec![],
+        expires: Expiration::Never {},
     };
 
     // host1, opponent2
     let game12 = Game {
         host: host1.clone(),
         opponent: opponent2.clone(),
-        host_move: GameMove::Paper {},
+        status: GameStatus::Proposed,
+        host_move: Some(GameMove::Paper {}),
+        host_commitment: None,
         opponent_move: None,
         result: None,
+        wager: None,
+        host_nft: None,
+        opponent_collection: None,
+        opponent_nft: None,
+        opponent_nft_accepted: false,
+        bond: None,
+        ruleset: Ruleset::Classic {},
+        best_of: 1,
+        score: Score::default(),
+        host_hand: None,
+        opponent_hand: None,
+        minus_one: None,
+        beacon: None,
+        timeout: DEFAULT_GAME_TIMEOUT,
+        counter_offers: vec![],
+        expires: Expiration::Never {},
     };
 
     // host2, opponent2
     let game22 = Game {
         host: host2.clone(),
         opponent: opponent2.clone(),
-        host_move: GameMove::Paper {},
+        status: GameStatus::Proposed,
+        host_move: Some(GameMove::Paper {}),
+        host_commitment: None,
         opponent_move: None,
         result: None,
+        wager: None,
+        host_nft: None,
+        opponent_collection: None,
+        opponent_nft: None,
+        opponent_nft_accepted: false,
+        bond: None,
+        ruleset: Ruleset::Classic {},
+        best_of: 1,
+        score: Score::default(),
+        host_hand: None,
+        opponent_hand: None,
+        minus_one: None,
+        beacon: None,
+        timeout: DEFAULT_GAME_TIMEOUT,
+        counter_offers: vec![],
+        expires: Expiration::Never {},
     };
 
     games()
@@ -544,7 +849,7 @@ fn game_indexedmap() {
 
     // load all games
     let list = games()
-        .range(&mut deps.storage, None, None, Order::Ascending)
+        .range(&deps.storage, None, None, Order::Ascending)
         .collect::<Result<Vec<_>, _>>()
         .unwrap();
 
@@ -559,7 +864,7 @@ fn game_indexedmap() {
         .idx
         .host
         .prefix(host1.clone())
-        .range(&mut deps.storage, None, None, Order::Ascending)
+        .range(&deps.storage, None, None, Order::Ascending)
         .collect::<Result<Vec<(_, _)>, _>>()
         .unwrap();
 
@@ -574,7 +879,7 @@ fn game_indexedmap() {
         .idx
         .opponent
         .prefix(opponent2.clone())
-        .range(&mut deps.storage, None, None, Order::Ascending)
+        .range(&deps.storage, None, None, Order::Ascending)
         .map(|kv_item| kv_item.unwrap().1)
         .collect::<Vec<_>>();
 
@@ -583,3 +888,3631 @@ fn game_indexedmap() {
     assert_eq!(list[0], game12);
     assert_eq!(2, list.len());
 }
+
+// build the message a cw721 contract sends us after SendNft
+fn send_nft(sender: &str, token_id: &str, msg: NftReceiveMsg) -> ExecuteMsg {
+    ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+        sender: sender.to_string(),
+        token_id: token_id.to_string(),
+        msg: to_binary(&msg).unwrap(),
+    })
+}
+
+// the TransferNft message we expect the contract to emit
+fn nft_transfer(collection: &str, token_id: &str, recipient: &str) -> CosmosMsg {
+    WasmMsg::Execute {
+        contract_addr: collection.to_string(),
+        msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+            recipient: recipient.to_string(),
+            token_id: token_id.to_string(),
+        })
+        .unwrap(),
+        funds: vec![],
+    }
+    .into()
+}
+
+// single round classic challenge staked with NFTs
+fn nft_challenge(opponent_collection: Option<&str>, timeout: Option<Duration>) -> NftReceiveMsg {
+    NftReceiveMsg::ProposeGame {
+        opponent: Addr::unchecked("opponent"),
+        terms: GameTerms {
+            wager: None,
+            ruleset: Ruleset::Classic {},
+            best_of: 1,
+            timeout,
+        },
+        opponent_collection: opponent_collection.map(Addr::unchecked),
+    }
+}
+
+fn accept_with_nft() -> NftReceiveMsg {
+    NftReceiveMsg::AcceptChallenge {
+        host: Addr::unchecked("host"),
+    }
+}
+
+// instantiates with "admin", who approves the collections
+fn approve_collections(mut deps: DepsMut, collections: &[&str]) {
+    let msg = InstantiateMsg {
+        admin: Addr::unchecked("admin"),
+        allowlist_enabled: false,
+    };
+    instantiate(deps.branch(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+    for collection in collections {
+        let msg = ExecuteMsg::AddNftCollection {
+            addr: Addr::unchecked(*collection),
+        };
+        execute(deps.branch(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+    }
+}
+
+#[test]
+fn only_approved_collections_send_nfts() {
+    let mut deps = mock_dependencies();
+    approve_collections(deps.as_mut(), &["punks"]);
+    for player in ["victim", "attacker"] {
+        let info = mock_info(player, &coins(100, "token"));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {}).unwrap();
+    }
+
+    // a fake collection can't start or accept a game in someone else's name
+    let forged = NftReceiveMsg::ProposeGame {
+        opponent: Addr::unchecked("attacker"),
+        terms: GameTerms {
+            wager: Some(coin(100, "token")),
+            ruleset: Ruleset::Classic {},
+            best_of: 1,
+            timeout: None,
+        },
+        opponent_collection: Some(Addr::unchecked("evil")),
+    };
+    let msg = send_nft("victim", "fake1", forged);
+    let err = execute(deps.as_mut(), mock_env(), mock_info("evil", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::UnknownNftCollection {
+            addr: Addr::unchecked("evil")
+        }
+    );
+    let msg = ExecuteMsg::ProposeGame {
+        opponent: Addr::unchecked("victim"),
+        terms: GameTerms {
+            wager: Some(coin(100, "token")),
+            ruleset: Ruleset::Classic {},
+            best_of: 1,
+            timeout: None,
+        },
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("attacker", &[]), msg).unwrap();
+    let forged = NftReceiveMsg::AcceptChallenge {
+        host: Addr::unchecked("attacker"),
+    };
+    let msg = send_nft("victim", "fake2", forged);
+    let err = execute(deps.as_mut(), mock_env(), mock_info("evil", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::UnknownNftCollection {
+            addr: Addr::unchecked("evil")
+        }
+    );
+    assert_eq!(query_balance(deps.as_ref(), "victim"), coins(100, "token"));
+
+    // only the admin manages the collections
+    let msg = ExecuteMsg::AddNftCollection {
+        addr: Addr::unchecked("evil"),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("attacker", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::Admin(AdminError::NotAdmin {})
+    );
+    let msg = QueryMsg::GetNftCollections {
+        start_after: None,
+        limit: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let collections: NftCollectionsResponse = from_binary(&res).unwrap();
+    assert_eq!(collections.collections, [Addr::unchecked("punks")]);
+}
+
+#[test]
+fn nft_game_winner_takes_both() {
+    let mut deps = mock_dependencies();
+    approve_collections(deps.as_mut(), &["punks", "apes"]);
+    let host = mock_info("host", &[]);
+    let opponent = mock_info("opponent", &[]);
+
+    // "host" stakes "punk1" from "punks", opponent has to stake from "apes"
+    let info = mock_info("punks", &[]);
+    let msg = send_nft("host", "punk1", nft_challenge(Some("apes"), None));
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    // stake is recorded with the game
+    let game = games()
+        .load(
+            &deps.storage,
+            (Addr::unchecked("host"), Addr::unchecked("opponent")),
+        )
+        .unwrap();
+    assert_eq!(game.host_nft.unwrap().token_id, "punk1");
+
+    // accepting without a stake should fail
+    let msg = ExecuteMsg::AcceptChallenge {
+        host: Addr::unchecked("host"),
+    };
+    let err = execute(deps.as_mut(), mock_env(), opponent.clone(), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::NftStakeRequired {});
+
+    // accepting with an NFT from another collection should fail
+    let info = mock_info("punks", &[]);
+    let msg = send_nft("opponent", "punk2", accept_with_nft());
+    let err = execute(deps.as_mut(), mock_env(), info, msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::NftCollectionNotAllowed {
+            collection: Addr::unchecked("punks")
+        }
+    );
+
+    // accepting with an NFT from "apes" and winning move, opponent gets both
+    let info = mock_info("apes", &[]);
+    let msg = send_nft("opponent", "ape7", accept_with_nft());
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let msg = commit(GameMove::Rock {}, "salt");
+    execute(deps.as_mut(), mock_env(), host.clone(), msg).unwrap();
+    let msg = answer(GameMove::Paper {});
+    execute(deps.as_mut(), mock_env(), opponent, msg).unwrap();
+    let msg = reveal(GameMove::Rock {}, "salt");
+    let res = execute(deps.as_mut(), mock_env(), host, msg).unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("game_result", "Opponent won")));
+    assert_eq!(
+        res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>(),
+        vec![
+            nft_transfer("punks", "punk1", "opponent"),
+            nft_transfer("apes", "ape7", "opponent"),
+        ]
+    );
+}
+
+#[test]
+fn nft_game_tie_returns_stakes() {
+    let mut deps = mock_dependencies();
+    approve_collections(deps.as_mut(), &["punks", "apes", "junk"]);
+    let host = mock_info("host", &[]);
+
+    // any NFT can be staked, but the host has to accept it before playing
+    let info = mock_info("punks", &[]);
+    let msg = send_nft("host", "punk1", nft_challenge(None, None));
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("apes", &[]);
+    let msg = send_nft("opponent", "ape7", accept_with_nft());
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let msg = commit(GameMove::Rock {}, "salt");
+    let err = execute(deps.as_mut(), mock_env(), host.clone(), msg.clone());
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::NftNotAccepted {});
+    let accept_nft = ExecuteMsg::AcceptNft {
+        opponent: Addr::unchecked("opponent"),
+    };
+    execute(deps.as_mut(), mock_env(), host.clone(), accept_nft).unwrap();
+    execute(deps.as_mut(), mock_env(), host.clone(), msg).unwrap();
+    let msg = answer(GameMove::Rock {});
+    execute(deps.as_mut(), mock_env(), mock_info("opponent", &[]), msg).unwrap();
+    let msg = reveal(GameMove::Rock {}, "salt");
+    let res = execute(deps.as_mut(), mock_env(), host.clone(), msg).unwrap();
+    assert!(res.attributes.contains(&attr("game_result", "Tie")));
+    assert_eq!(
+        res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>(),
+        vec![
+            nft_transfer("punks", "punk1", "host"),
+            nft_transfer("apes", "ape7", "opponent"),
+        ]
+    );
+
+    // a rejected NFT calls the game off
+    let info = mock_info("punks", &[]);
+    let msg = send_nft("host", "punk1", nft_challenge(None, None));
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let info = mock_info("junk", &[]);
+    let msg = send_nft("opponent", "junk1", accept_with_nft());
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let reject_nft = ExecuteMsg::RejectNft {
+        opponent: Addr::unchecked("opponent"),
+    };
+    let res = execute(deps.as_mut(), mock_env(), host, reject_nft).unwrap();
+    assert_eq!(
+        res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>(),
+        vec![
+            nft_transfer("punks", "punk1", "host"),
+            nft_transfer("junk", "junk1", "opponent"),
+        ]
+    );
+}
+
+#[test]
+fn expired_nft_game_releases_stake() {
+    let mut deps = mock_dependencies();
+    approve_collections(deps.as_mut(), &["punks", "apes"]);
+
+    let info = mock_info("punks", &[]);
+    let msg = send_nft(
+        "host",
+        "punk1",
+        nft_challenge(None, Some(Duration::Time(60))),
+    );
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // game can't be expired before timeout
+    let info = mock_info("anyone", &[]);
+    let msg = ExecuteMsg::ExpireGame {
+        host: Addr::unchecked("host"),
+        opponent: Addr::unchecked("opponent"),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::GameNotExpired {});
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(61);
+
+    // opponent is too late now
+    let msg_late = send_nft("opponent", "ape7", accept_with_nft());
+    let err = execute(deps.as_mut(), env.clone(), mock_info("apes", &[]), msg_late);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::GameExpired {});
+
+    // but anyone can expire the game, which returns the NFT to host
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>(),
+        vec![nft_transfer("punks", "punk1", "host")]
+    );
+    assert!(games()
+        .may_load(
+            &deps.storage,
+            (Addr::unchecked("host"), Addr::unchecked("opponent"))
+        )
+        .unwrap()
+        .is_none());
+}
+
+fn place_bet(outcome: GameResult) -> ExecuteMsg {
+    ExecuteMsg::PlaceBet {
+        host: Addr::unchecked("host"),
+        opponent: Addr::unchecked("opponent"),
+        outcome,
+    }
+}
+
+#[test]
+fn spectator_bets_are_paid_out_pro_rata() {
+    let mut deps = mock_dependencies();
+
+    // betting on a game that doesn't exist should fail
+    let info = mock_info("alice", &coins(10, "token"));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        place_bet(GameResult::OpponentWins {}),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::GameNotFound {});
+
+    // a plain game shows the host move, the opponent could bet on the result they pick
+    let info = mock_info("host", &[]);
+    let msg = ExecuteMsg::StartGame {
+        opponent: Addr::unchecked("opponent"),
+        host_move: GameMove::Scissors {},
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let info = mock_info("alice", &coins(10, "token"));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        place_bet(GameResult::OpponentWins {}),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::BettingClosed {});
+    let msg = ExecuteMsg::DeclineChallenge {
+        host: Addr::unchecked("host"),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("opponent", &[]), msg).unwrap();
+
+    // in a challenge the host move stays hidden until the opponent played
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        propose(None),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &[]),
+        accept(),
+    )
+    .unwrap();
+    let msg = commit(GameMove::Scissors {}, "salt");
+    execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg).unwrap();
+
+    // players can't bet on their own game
+    let info = mock_info("host", &coins(10, "token"));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        place_bet(GameResult::HostWins {}),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::PlayerCannotBet {});
+
+    // "alice" 10 and "carol" 20 on opponent, "bob" 30 on host
+    for (bettor, amount, outcome) in [
+        ("alice", 10, GameResult::OpponentWins {}),
+        ("bob", 30, GameResult::HostWins {}),
+        ("carol", 20, GameResult::OpponentWins {}),
+    ] {
+        let info = mock_info(bettor, &coins(amount, "token"));
+        execute(deps.as_mut(), mock_env(), info, place_bet(outcome)).unwrap();
+    }
+
+    // pool has a single denom
+    let info = mock_info("dave", &coins(5, "other"));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        place_bet(GameResult::Tie {}),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::WrongBetDenom {
+            denom: "token".to_string()
+        }
+    );
+
+    // "bob" can't hedge on the other side
+    let info = mock_info("bob", &coins(5, "token"));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        place_bet(GameResult::Tie {}),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::ConflictingBet {});
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::GetBetPool {
+            host: Addr::unchecked("host"),
+            opponent: Addr::unchecked("opponent"),
+        },
+    )
+    .unwrap();
+    let pool = from_binary::<BetPoolResponse>(&res).unwrap().pool.unwrap();
+    assert_eq!(pool.opponent_wins, Uint128::new(30));
+    assert_eq!(pool.total(), Uint128::new(60));
+
+    // opponent wins, "alice" and "carol" split the pool of 60
+    let msg = answer(GameMove::Rock {});
+    execute(deps.as_mut(), mock_env(), mock_info("opponent", &[]), msg).unwrap();
+    let msg = reveal(GameMove::Scissors {}, "salt");
+    let res = execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>(),
+        vec![
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "alice".to_string(),
+                amount: coins(20, "token"),
+            }),
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "carol".to_string(),
+                amount: coins(40, "token"),
+            }),
+        ]
+    );
+
+    // pool is gone with the game
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::GetBetPool {
+            host: Addr::unchecked("host"),
+            opponent: Addr::unchecked("opponent"),
+        },
+    )
+    .unwrap();
+    assert_eq!(from_binary::<BetPoolResponse>(&res).unwrap().pool, None);
+}
+
+#[test]
+fn spectator_bets_are_refunded_without_winners() {
+    let mut deps = mock_dependencies();
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        propose(None),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &[]),
+        accept(),
+    )
+    .unwrap();
+    let msg = commit(GameMove::Scissors {}, "salt");
+    execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg).unwrap();
+
+    let info = mock_info("alice", &coins(10, "token"));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        place_bet(GameResult::Tie {}),
+    )
+    .unwrap();
+
+    // nobody backed opponent winning, so "alice" is refunded
+    let msg = answer(GameMove::Rock {});
+    execute(deps.as_mut(), mock_env(), mock_info("opponent", &[]), msg).unwrap();
+    let msg = reveal(GameMove::Scissors {}, "salt");
+    let res = execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>(),
+        vec![CosmosMsg::Bank(BankMsg::Send {
+            to_address: "alice".to_string(),
+            amount: coins(10, "token"),
+        })]
+    );
+}
+
+#[test]
+fn bettors_per_game_are_capped() {
+    let mut deps = mock_dependencies();
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        propose(None),
+    )
+    .unwrap();
+
+    for i in 0..MAX_BETTORS {
+        let info = mock_info(&format!("bettor{}", i), &coins(1, "token"));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            place_bet(GameResult::HostWins {}),
+        )
+        .unwrap();
+    }
+    let info = mock_info("latecomer", &coins(1, "token"));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        place_bet(GameResult::HostWins {}),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::TooManyBettors { max: MAX_BETTORS }
+    );
+
+    // bettors already in the pool can still top up
+    let info = mock_info("bettor0", &coins(1, "token"));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        place_bet(GameResult::HostWins {}),
+    )
+    .unwrap();
+}
+
+fn query_balance(deps: Deps, player: &str) -> Vec<Coin> {
+    let res = query(
+        deps,
+        mock_env(),
+        QueryMsg::GetBalance {
+            player: Addr::unchecked(player),
+        },
+    )
+    .unwrap();
+    from_binary::<BalanceResponse>(&res).unwrap().balances
+}
+
+#[test]
+fn wagers_are_played_from_balance() {
+    let mut deps = mock_dependencies();
+
+    // "host" deposits once
+    let info = mock_info("host", &coins(100, "token"));
+    execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {}).unwrap();
+    assert_eq!(query_balance(deps.as_ref(), "host"), coins(100, "token"));
+
+    // wager more than the balance should fail
+    let info = mock_info("host", &[]);
+    let msg = propose(Some(coin(150, "token")));
+    let err = execute(deps.as_mut(), mock_env(), info, msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::InsufficientBalance {
+            denom: "token".to_string(),
+            available: Uint128::new(100)
+        }
+    );
+
+    // propose game with wager of 40, no funds sent
+    let info = mock_info("host", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        propose(Some(coin(40, "token"))),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 0);
+    assert_eq!(query_balance(deps.as_ref(), "host"), coins(60, "token"));
+
+    // opponent with empty balance can't match the wager
+    let info = mock_info("opponent", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, accept());
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::InsufficientBalance {
+            denom: "token".to_string(),
+            available: Uint128::zero()
+        }
+    );
+
+    // funds sent along are deposited first, host wins the pot of 80
+    let info = mock_info("opponent", &coins(50, "token"));
+    execute(deps.as_mut(), mock_env(), info, accept()).unwrap();
+    let msg = commit(GameMove::Rock {}, "salt");
+    execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg).unwrap();
+    let msg = answer(GameMove::Scissors {});
+    execute(deps.as_mut(), mock_env(), mock_info("opponent", &[]), msg).unwrap();
+    let msg = reveal(GameMove::Rock {}, "salt");
+    let res = execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 0);
+    assert!(res.attributes.contains(&attr("game_result", "Host won")));
+    assert_eq!(query_balance(deps.as_ref(), "host"), coins(140, "token"));
+    assert_eq!(query_balance(deps.as_ref(), "opponent"), coins(10, "token"));
+
+    // withdraw part of the winnings
+    let info = mock_info("host", &[]);
+    let msg = ExecuteMsg::Withdraw {
+        amount: coin(100, "token"),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "host".to_string(),
+            amount: coins(100, "token"),
+        })
+    );
+    assert_eq!(query_balance(deps.as_ref(), "host"), coins(40, "token"));
+
+    // emptied balances disappear from the query
+    let info = mock_info("opponent", &[]);
+    let msg = ExecuteMsg::Withdraw {
+        amount: coin(10, "token"),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(query_balance(deps.as_ref(), "opponent"), vec![]);
+}
+
+#[test]
+fn expired_game_refunds_wager() {
+    let mut deps = mock_dependencies();
+
+    let info = mock_info("host", &coins(40, "token"));
+    let msg = propose(Some(coin(40, "token")));
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(query_balance(deps.as_ref(), "host"), vec![]);
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(24 * 60 * 60 + 1);
+    let info = mock_info("anyone", &[]);
+    let msg = ExecuteMsg::ExpireGame {
+        host: Addr::unchecked("host"),
+        opponent: Addr::unchecked("opponent"),
+    };
+    execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(query_balance(deps.as_ref(), "host"), coins(40, "token"));
+}
+
+#[test]
+fn failed_payouts_can_be_claimed() {
+    let mut deps = mock_dependencies();
+    approve_collections(deps.as_mut(), &["punks", "apes"]);
+
+    // NFT game with a spectator bet
+    let info = mock_info("punks", &[]);
+    let msg = send_nft("host", "punk1", nft_challenge(Some("apes"), None));
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let info = mock_info("alice", &coins(10, "token"));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        place_bet(GameResult::OpponentWins {}),
+    )
+    .unwrap();
+
+    // opponent wins, payouts go out as submessages
+    let info = mock_info("apes", &[]);
+    let msg = send_nft("opponent", "ape7", accept_with_nft());
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let msg = commit(GameMove::Rock {}, "salt");
+    execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg).unwrap();
+    let msg = answer(GameMove::Paper {});
+    execute(deps.as_mut(), mock_env(), mock_info("opponent", &[]), msg).unwrap();
+    let msg = reveal(GameMove::Rock {}, "salt");
+    let res = execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg).unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("game_result", "Opponent won")));
+    assert_eq!(res.messages.len(), 3);
+
+    // "punk1" transfer fails (e.g. recipient contract rejects it),
+    // the game is still over and the NFT is kept for the opponent
+    let failed = Reply {
+        id: res.messages[0].id,
+        result: ContractResult::Err("blocked recipient".to_string()),
+    };
+    reply(deps.as_mut(), mock_env(), failed).unwrap();
+
+    // the other two were delivered
+    for sub_msg in &res.messages[1..] {
+        let delivered = Reply {
+            id: sub_msg.id,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![],
+                data: None,
+            }),
+        };
+        reply(deps.as_mut(), mock_env(), delivered).unwrap();
+    }
+    assert!(PENDING_PAYOUTS
+        .range(&deps.storage, None, None, Order::Ascending)
+        .next()
+        .is_none());
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::GetClaims {
+            player: Addr::unchecked("opponent"),
+        },
+    )
+    .unwrap();
+    let claims: ClaimsResponse = from_binary(&res).unwrap();
+    assert_eq!(claims.coins, vec![]);
+    assert_eq!(
+        claims.nfts,
+        vec![EscrowedNft {
+            contract: Addr::unchecked("punks"),
+            token_id: "punk1".to_string(),
+        }]
+    );
+
+    // opponent claims the NFT
+    let info = mock_info("opponent", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::Claim {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>(),
+        vec![nft_transfer("punks", "punk1", "opponent")]
+    );
+
+    // and there's nothing left to claim
+    let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Claim {});
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::NothingToClaim {});
+}
+
+#[test]
+fn solvency_report() {
+    let mut deps = mock_dependencies();
+
+    // "host" deposits 100 and wagers 40 of it, "alice" bets 10 on the game
+    let info = mock_info("host", &coins(100, "token"));
+    let msg = propose(Some(coin(40, "token")));
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let info = mock_info("alice", &coins(10, "token"));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        place_bet(GameResult::Tie {}),
+    )
+    .unwrap();
+
+    // the bank got all of it
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(110, "token"));
+    assert_solvent(deps.as_ref(), &mock_env()).unwrap();
+
+    let msg = QueryMsg::GetSolvencyReport {
+        cursor: None,
+        limit: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let report: SolvencyReportResponse = from_binary(&res).unwrap();
+    assert!(report.solvent);
+    assert_eq!(report.next, None);
+    assert_eq!(report.denoms.len(), 1);
+    assert_eq!(report.denoms[0].game_escrow, Uint128::new(40));
+    assert_eq!(report.denoms[0].bet_pools, Uint128::new(10));
+    assert_eq!(report.denoms[0].balances, Uint128::new(60));
+    assert_eq!(report.denoms[0].liabilities, Uint128::new(110));
+
+    // one entry per page: the game, the bet pool, then "host"'s balance
+    let mut pages = 0;
+    let mut cursor = None;
+    let paged = loop {
+        let msg = QueryMsg::GetSolvencyReport {
+            cursor,
+            limit: Some(1),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let page: SolvencyReportResponse = from_binary(&res).unwrap();
+        pages += 1;
+        match page.next {
+            Some(next) => cursor = Some(next),
+            None => break page,
+        }
+    };
+    assert_eq!(pages, 3);
+    assert_eq!(paged, report);
+
+    // something went missing
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(95, "token"));
+    let msg = QueryMsg::GetSolvencyReport {
+        cursor: None,
+        limit: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let report: SolvencyReportResponse = from_binary(&res).unwrap();
+    assert!(!report.solvent);
+    assert_eq!(
+        assert_solvent(deps.as_ref(), &mock_env()).unwrap_err(),
+        ContractError::Insolvent {
+            denom: "token".to_string(),
+            shortfall: Uint128::new(15)
+        }
+    );
+}
+
+#[test]
+fn hooks_are_notified_about_game_lifecycle() {
+    let mut deps = mock_dependencies();
+
+    let info = mock_info("creator", &[]);
+    let msg = InstantiateMsg {
+        admin: Addr::unchecked("admin"),
+        allowlist_enabled: false,
+    };
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // only admin can register subscribers
+    let msg = ExecuteMsg::AddHook {
+        addr: Addr::unchecked("rewards"),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        msg.clone(),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<HookError>().unwrap();
+    assert_eq!(*err_unwrapped, HookError::Admin(AdminError::NotAdmin {}));
+    execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::GetHooks {}).unwrap();
+    let hooks: HooksResponse = from_binary(&res).unwrap();
+    assert_eq!(hooks.hooks, ["rewards"]);
+
+    // starting a game notifies "rewards"
+    let info = mock_info("host", &[]);
+    let msg = ExecuteMsg::StartGame {
+        opponent: Addr::unchecked("opponent"),
+        host_move: GameMove::Rock {},
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        RpsHookMsg::GameStarted {
+            host: Addr::unchecked("host"),
+            opponent: Addr::unchecked("opponent"),
+            wager: None,
+        }
+        .into_cosmos_msg("rewards")
+        .unwrap()
+    );
+
+    // and so does ending it
+    let info = mock_info("opponent", &[]);
+    let msg = ExecuteMsg::EndGame {
+        host: Addr::unchecked("host"),
+        opponent_move: GameMove::Paper {},
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        RpsHookMsg::GameEnded {
+            host: Addr::unchecked("host"),
+            opponent: Addr::unchecked("opponent"),
+            result: GameResult::OpponentWins {},
+        }
+        .into_cosmos_msg("rewards")
+        .unwrap()
+    );
+
+    // expired games are reported as cancelled
+    let info = mock_info("host", &[]);
+    let msg = ExecuteMsg::StartGame {
+        opponent: Addr::unchecked("opponent"),
+        host_move: GameMove::Rock {},
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(24 * 60 * 60 + 1);
+    let msg = ExecuteMsg::ExpireGame {
+        host: Addr::unchecked("host"),
+        opponent: Addr::unchecked("opponent"),
+    };
+    let res = execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::reply_on_error(
+            RpsHookMsg::GameCancelled {
+                host: Addr::unchecked("host"),
+                opponent: Addr::unchecked("opponent"),
+            }
+            .into_cosmos_msg("rewards")
+            .unwrap(),
+            HOOK_REPLY_ID
+        )
+    );
+
+    // a failing subscriber is ignored
+    let failed = Reply {
+        id: HOOK_REPLY_ID,
+        result: ContractResult::Err("out of gas".to_string()),
+    };
+    let res = reply(deps.as_mut(), mock_env(), failed).unwrap();
+    assert!(res.attributes.contains(&attr("hook_error", "out of gas")));
+}
+
+#[test]
+fn temporary_ban_lapses() {
+    let mut deps = mock_dependencies();
+
+    let info = mock_info("creator", &[]);
+    let msg = InstantiateMsg {
+        admin: Addr::unchecked("admin"),
+        allowlist_enabled: false,
+    };
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // only admin or moderators can ban
+    let msg = ExecuteMsg::AddToBlacklist {
+        addr: Addr::unchecked("spammer"),
+        reason: Some("spam challenges".to_string()),
+        expires: Some(Expiration::AtHeight(mock_env().block.height + 100)),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        msg.clone(),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::Unauthorized {});
+    execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+    // ban is recorded with the reason and who banned
+    let blacklist = query_blacklist(deps.as_ref());
+    let ban = &blacklist.bans[0];
+    assert_eq!(ban.addr, "spammer");
+    assert_eq!(ban.ban.reason, Some("spam challenges".to_string()));
+    assert_eq!(ban.ban.banned_by, "admin");
+    assert_eq!(ban.ban.banned_at, mock_env().block.time);
+    assert!(ban.active);
+
+    let start = ExecuteMsg::StartGame {
+        opponent: Addr::unchecked("victim"),
+        host_move: GameMove::Rock {},
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("spammer", &[]),
+        start.clone(),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::Blacklisted {
+            addr: Addr::unchecked("spammer")
+        }
+    );
+
+    // 100 blocks later the ban lapsed on its own
+    let mut env = mock_env();
+    env.block.height += 100;
+    execute(deps.as_mut(), env.clone(), mock_info("spammer", &[]), start).unwrap();
+    let msg = QueryMsg::GetBlacklist {
+        start_after: None,
+        limit: None,
+    };
+    let blacklist: BlacklistResponse =
+        from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+    assert!(!blacklist.bans[0].active);
+
+    // removing an address that isn't banned fails
+    let msg = ExecuteMsg::RemoveFromBlacklist {
+        addr: Addr::unchecked("victim"),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::NotBlacklisted {
+            addr: Addr::unchecked("victim")
+        }
+    );
+}
+
+#[test]
+fn allowlist_mode_gates_games() {
+    let mut deps = mock_dependencies();
+
+    // private deployment, allowlist enabled from the start
+    let info = mock_info("creator", &[]);
+    let msg = InstantiateMsg {
+        admin: Addr::unchecked("admin"),
+        allowlist_enabled: true,
+    };
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let start = ExecuteMsg::StartGame {
+        opponent: Addr::unchecked("bob"),
+        host_move: GameMove::Rock {},
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("alice", &[]),
+        start.clone(),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::NotAllowlisted {
+            addr: Addr::unchecked("alice")
+        }
+    );
+
+    // admin adds "alice" only
+    let msg = ExecuteMsg::AddToAllowlist {
+        addr: Addr::unchecked("alice"),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), start).unwrap();
+
+    // "bob" isn't on the allowlist, so they can't answer
+    let end = ExecuteMsg::EndGame {
+        host: Addr::unchecked("alice"),
+        opponent_move: GameMove::Paper {},
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("bob", &[]),
+        end.clone(),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::NotAllowlisted {
+            addr: Addr::unchecked("bob")
+        }
+    );
+
+    let msg = QueryMsg::GetAllowlist {
+        start_after: None,
+        limit: None,
+    };
+    let allowlist: AllowlistResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(allowlist.addrs, [Addr::unchecked("alice")]);
+
+    // only admin can switch the mode, once off anyone can play
+    let msg = ExecuteMsg::UpdateConfig {
+        allowlist_enabled: Some(false),
+        game_bond: None,
+        max_open_games: None,
+        max_fee_bps: None,
+        treasury: None,
+        timeout_limits: None,
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("bob", &[]),
+        msg.clone(),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::Admin(AdminError::NotAdmin {})
+    );
+    execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), end).unwrap();
+}
+
+#[test]
+fn moderator_cannot_take_over() {
+    let mut deps = mock_dependencies();
+
+    let info = mock_info("creator", &[]);
+    let msg = InstantiateMsg {
+        admin: Addr::unchecked("admin"),
+        allowlist_enabled: false,
+    };
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // only admin can grant roles
+    let grant = ExecuteMsg::GrantRole {
+        role: Role::Moderator,
+        addr: Addr::unchecked("mod"),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("mod", &[]),
+        grant.clone(),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::Admin(AdminError::NotAdmin {})
+    );
+    execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), grant).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::GetRoles {}).unwrap();
+    let roles: RolesResponse = from_binary(&res).unwrap();
+    assert_eq!(roles.admin, Some("admin".to_string()));
+    assert_eq!(roles.moderators, [Addr::unchecked("mod")]);
+    assert_eq!(roles.fee_managers, Vec::<Addr>::new());
+
+    // moderator manages the blacklist, ban records who did it
+    let msg = ExecuteMsg::AddToBlacklist {
+        addr: Addr::unchecked("spammer"),
+        reason: None,
+        expires: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("mod", &[]), msg).unwrap();
+    let blacklist = query_blacklist(deps.as_ref());
+    assert_eq!(blacklist.bans[0].ban.banned_by, "mod");
+
+    // moderator cancels an abusive game, host gets the wager back
+    let info = mock_info("host", &coins(40, "token"));
+    let msg = propose(Some(coin(40, "token")));
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let cancel = ExecuteMsg::CancelGame {
+        host: Addr::unchecked("host"),
+        opponent: Addr::unchecked("opponent"),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        cancel.clone(),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::Unauthorized {});
+    execute(deps.as_mut(), mock_env(), mock_info("mod", &[]), cancel).unwrap();
+    assert_eq!(query_balance(deps.as_ref(), "host"), coins(40, "token"));
+
+    // but can't touch config, roles or the fee
+    let msgs = vec![
+        ExecuteMsg::UpdateConfig {
+            allowlist_enabled: Some(true),
+            game_bond: None,
+            max_open_games: None,
+            max_fee_bps: None,
+            treasury: None,
+            timeout_limits: None,
+        },
+        ExecuteMsg::GrantRole {
+            role: Role::FeeManager,
+            addr: Addr::unchecked("mod"),
+        },
+        ExecuteMsg::UpdateFee { fee_bps: 100 },
+    ];
+    for msg in msgs {
+        let err = execute(deps.as_mut(), mock_env(), mock_info("mod", &[]), msg);
+        assert!(err.is_err());
+    }
+
+    // once revoked, moderator loses access
+    let msg = ExecuteMsg::RevokeRole {
+        role: Role::Moderator,
+        addr: Addr::unchecked("mod"),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+    let msg = ExecuteMsg::RemoveFromBlacklist {
+        addr: Addr::unchecked("spammer"),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("mod", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::Unauthorized {});
+}
+
+#[test]
+fn fee_manager_collects_house_fee() {
+    let mut deps = mock_dependencies();
+
+    let info = mock_info("creator", &[]);
+    let msg = InstantiateMsg {
+        admin: Addr::unchecked("admin"),
+        allowlist_enabled: false,
+    };
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let msg = ExecuteMsg::GrantRole {
+        role: Role::FeeManager,
+        addr: Addr::unchecked("treasurer"),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+    // the fee manager can't go above the admin's cap, 10% by default
+    let info = mock_info("treasurer", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::UpdateFee { fee_bps: 1001 },
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::FeeAboveMax { max: 1000 });
+    let msg = ExecuteMsg::UpdateConfig {
+        allowlist_enabled: None,
+        game_bond: None,
+        max_open_games: None,
+        max_fee_bps: Some(10_001),
+        treasury: None,
+        timeout_limits: None,
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::InvalidFee {});
+    // 5% fee
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::UpdateFee { fee_bps: 500 },
+    )
+    .unwrap();
+
+    // host wins the pot of 200 minus 10 fee
+    let info = mock_info("host", &coins(100, "token"));
+    let msg = propose(Some(coin(100, "token")));
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let info = mock_info("opponent", &coins(100, "token"));
+    execute(deps.as_mut(), mock_env(), info, accept()).unwrap();
+    let msg = commit(GameMove::Rock {}, "salt");
+    execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg).unwrap();
+    let msg = answer(GameMove::Scissors {});
+    execute(deps.as_mut(), mock_env(), mock_info("opponent", &[]), msg).unwrap();
+    let msg = reveal(GameMove::Rock {}, "salt");
+    execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg).unwrap();
+    assert_eq!(query_balance(deps.as_ref(), "host"), coins(190, "token"));
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::GetTreasury {}).unwrap();
+    let treasury: TreasuryResponse = from_binary(&res).unwrap();
+    assert_eq!(treasury.fees, coins(10, "token"));
+
+    // collected fee is withdrawn by the fee manager only, to the admin's treasury
+    let msg = ExecuteMsg::WithdrawFees {
+        amount: coin(10, "token"),
+    };
+    let info = mock_info("treasurer", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::NoTreasury {});
+    let config = ExecuteMsg::UpdateConfig {
+        allowlist_enabled: None,
+        game_bond: None,
+        max_open_games: None,
+        max_fee_bps: None,
+        treasury: Some(Addr::unchecked("dao")),
+        timeout_limits: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), config).unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        msg.clone(),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::Unauthorized {});
+    let res = execute(deps.as_mut(), mock_env(), mock_info("treasurer", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "dao".to_string(),
+            amount: coins(10, "token"),
+        })
+    );
+}
+
+#[test]
+fn privileged_actions_are_audited() {
+    let mut deps = mock_dependencies();
+
+    let info = mock_info("creator", &[]);
+    let msg = InstantiateMsg {
+        admin: Addr::unchecked("admin"),
+        allowlist_enabled: false,
+    };
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::GrantRole {
+        role: Role::Moderator,
+        addr: Addr::unchecked("mod"),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+    let msg = ExecuteMsg::AddToBlacklist {
+        addr: Addr::unchecked("spammer"),
+        reason: Some("spam".to_string()),
+        expires: None,
+    };
+    let mut env = mock_env();
+    env.block.height += 5;
+    execute(deps.as_mut(), env, mock_info("mod", &[]), msg).unwrap();
+    let msg = ExecuteMsg::UpdateConfig {
+        allowlist_enabled: Some(true),
+        game_bond: None,
+        max_open_games: None,
+        max_fee_bps: None,
+        treasury: None,
+        timeout_limits: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+    // failed attempts and regular player actions are not logged
+    let msg = ExecuteMsg::UpdateFee { fee_bps: 100 };
+    execute(deps.as_mut(), mock_env(), mock_info("mod", &[]), msg).unwrap_err();
+    let info = mock_info("host", &coins(10, "token"));
+    execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {}).unwrap();
+
+    let msg = QueryMsg::GetAuditLog {
+        start_after: None,
+        limit: None,
+        actor: None,
+    };
+    let log: AuditLogResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    let actions = log
+        .entries
+        .iter()
+        .map(|e| (e.id, e.entry.action.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        actions,
+        [
+            (1, "grant_role"),
+            (2, "add_to_blacklist"),
+            (3, "update_config")
+        ]
+    );
+    let ban = &log.entries[1].entry;
+    assert_eq!(ban.actor, "mod");
+    assert_eq!(ban.height, mock_env().block.height + 5);
+    assert_eq!(
+        ban.params,
+        vec![attr("addr", "spammer"), attr("reason", "spam")]
+    );
+
+    // filter by actor, paginated by id
+    let msg = QueryMsg::GetAuditLog {
+        start_after: Some(1),
+        limit: Some(1),
+        actor: Some(Addr::unchecked("admin")),
+    };
+    let log: AuditLogResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(log.entries.len(), 1);
+    assert_eq!(log.entries[0].id, 3);
+    assert_eq!(log.entries[0].entry.action, "update_config");
+}
+
+#[test]
+fn blocked_host_cannot_challenge() {
+    let mut deps = mock_dependencies();
+
+    // "alice" blocks "troll"
+    let msg = ExecuteMsg::BlockPlayer {
+        addr: Addr::unchecked("troll"),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+
+    let challenge = |opponent: &str| ExecuteMsg::StartGame {
+        opponent: Addr::unchecked(opponent),
+        host_move: GameMove::Rock {},
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("troll", &[]),
+        challenge("alice"),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::BlockedByPlayer {
+            addr: Addr::unchecked("alice")
+        }
+    );
+
+    // block is one-way and only affects "alice"
+    let info = mock_info("alice", &[]);
+    execute(deps.as_mut(), mock_env(), info, challenge("troll")).unwrap();
+    let info = mock_info("troll", &[]);
+    execute(deps.as_mut(), mock_env(), info, challenge("bob")).unwrap();
+
+    let msg = QueryMsg::GetBlockList {
+        player: Addr::unchecked("alice"),
+        start_after: None,
+        limit: None,
+    };
+    let res: BlockListResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.blocked, [Addr::unchecked("troll")]);
+
+    // after unblocking, challenges go through again
+    let msg = ExecuteMsg::UnblockPlayer {
+        addr: Addr::unchecked("troll"),
+    };
+    let info = mock_info("alice", &[]);
+    execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), info, msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::NotBlocked {
+            addr: Addr::unchecked("troll")
+        }
+    );
+    let info = mock_info("troll", &[]);
+    execute(deps.as_mut(), mock_env(), info, challenge("alice")).unwrap();
+}
+
+fn commit(host_move: GameMove, salt: &str) -> ExecuteMsg {
+    ExecuteMsg::CommitMove {
+        opponent: Addr::unchecked("opponent"),
+        commitment: move_commitment(&host_move, salt),
+    }
+}
+
+fn reveal(host_move: GameMove, salt: &str) -> ExecuteMsg {
+    ExecuteMsg::RevealMove {
+        opponent: Addr::unchecked("opponent"),
+        host_move,
+        salt: salt.to_string(),
+    }
+}
+
+// single round classic challenge, the way games with a wager are played
+fn propose(wager: Option<Coin>) -> ExecuteMsg {
+    ExecuteMsg::ProposeGame {
+        opponent: Addr::unchecked("opponent"),
+        terms: GameTerms {
+            wager,
+            ruleset: Ruleset::Classic {},
+            best_of: 1,
+            timeout: None,
+        },
+    }
+}
+
+fn accept() -> ExecuteMsg {
+    ExecuteMsg::AcceptChallenge {
+        host: Addr::unchecked("host"),
+    }
+}
+
+fn answer(opponent_move: GameMove) -> ExecuteMsg {
+    ExecuteMsg::EndGame {
+        host: Addr::unchecked("host"),
+        opponent_move,
+    }
+}
+
+#[test]
+fn challenge_is_played_as_series() {
+    let mut deps = mock_dependencies();
+    let host = mock_info("host", &[]);
+    let opponent = mock_info("opponent", &[]);
+
+    // even series length is rejected
+    let mut terms = GameTerms {
+        wager: Some(coin(50, "token")),
+        ruleset: Ruleset::Classic {},
+        best_of: 2,
+        timeout: None,
+    };
+    let msg = ExecuteMsg::ProposeGame {
+        opponent: Addr::unchecked("opponent"),
+        terms: terms.clone(),
+    };
+    let err = execute(deps.as_mut(), mock_env(), host.clone(), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::InvalidBestOf { max: 9 });
+
+    terms.best_of = 3;
+    let msg = ExecuteMsg::ProposeGame {
+        opponent: Addr::unchecked("opponent"),
+        terms,
+    };
+    let info = mock_info("host", &coins(50, "token"));
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // no moves before the opponent accepted
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        opponent.clone(),
+        answer(GameMove::Rock {}),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::ChallengeNotAccepted {});
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        host.clone(),
+        commit(GameMove::Rock {}, "salt"),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::WrongGameStatus {
+            status: GameStatus::Proposed
+        }
+    );
+
+    // accepting matches the wager
+    let msg = ExecuteMsg::AcceptChallenge {
+        host: Addr::unchecked("host"),
+    };
+    let info = mock_info("opponent", &coins(50, "token"));
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // round 1: host wins
+    let env = mock_env();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        host.clone(),
+        commit(GameMove::Rock {}, "s1"),
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        opponent.clone(),
+        answer(GameMove::Scissors {}),
+    )
+    .unwrap();
+    assert_eq!(res.attributes[1].value, "awaiting_reveal");
+    // revealing a different move than committed fails
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        host.clone(),
+        reveal(GameMove::Paper {}, "s1"),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::InvalidCommitment {});
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        host.clone(),
+        reveal(GameMove::Rock {}, "s1"),
+    )
+    .unwrap();
+    assert_eq!(res.attributes[1].value, "Host won");
+    assert_eq!(res.attributes[2].value, "1:0");
+
+    // round 2: tie is replayed
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        host.clone(),
+        commit(GameMove::Paper {}, "s2"),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        opponent.clone(),
+        answer(GameMove::Paper {}),
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        host.clone(),
+        reveal(GameMove::Paper {}, "s2"),
+    )
+    .unwrap();
+    assert_eq!(res.attributes[1].value, "Tie");
+    assert_eq!(res.attributes[2].value, "1:0");
+
+    let game = games()
+        .load(
+            &deps.storage,
+            (Addr::unchecked("host"), Addr::unchecked("opponent")),
+        )
+        .unwrap();
+    assert_eq!(game.status, GameStatus::Accepted);
+    assert_eq!(game.host_commitment, None);
+    assert_eq!(
+        game.score,
+        Score {
+            host: 1,
+            opponent: 0
+        }
+    );
+
+    // round 3: host wins the series and the pot
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        host.clone(),
+        commit(GameMove::Scissors {}, "s3"),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        opponent,
+        answer(GameMove::Paper {}),
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env,
+        host,
+        reveal(GameMove::Scissors {}, "s3"),
+    )
+    .unwrap();
+    assert_eq!(res.attributes[0].value, "try_revealmove");
+    assert_eq!(res.attributes[1].value, "Host won");
+    assert_eq!(query_balance(deps.as_ref(), "host"), coins(100, "token"));
+    assert_eq!(query_balance(deps.as_ref(), "opponent"), vec![]);
+}
+
+#[test]
+fn challenge_decline_and_reveal_timeout() {
+    let mut deps = mock_dependencies();
+    let propose = ExecuteMsg::ProposeGame {
+        opponent: Addr::unchecked("opponent"),
+        terms: GameTerms {
+            wager: Some(coin(50, "token")),
+            ruleset: Ruleset::Classic {},
+            best_of: 1,
+            timeout: Some(Duration::Height(10)),
+        },
+    };
+
+    // declined challenge refunds the host
+    let info = mock_info("host", &coins(50, "token"));
+    execute(deps.as_mut(), mock_env(), info, propose.clone()).unwrap();
+    let msg = ExecuteMsg::DeclineChallenge {
+        host: Addr::unchecked("host"),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("opponent", &[]), msg).unwrap();
+    assert_eq!(query_balance(deps.as_ref(), "host"), coins(50, "token"));
+
+    // host commits but never reveals
+    execute(deps.as_mut(), mock_env(), mock_info("host", &[]), propose).unwrap();
+    let msg = ExecuteMsg::AcceptChallenge {
+        host: Addr::unchecked("host"),
+    };
+    let info = mock_info("opponent", &coins(50, "token"));
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let info = mock_info("host", &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        commit(GameMove::Rock {}, "salt"),
+    )
+    .unwrap();
+    let info = mock_info("opponent", &[]);
+    execute(deps.as_mut(), mock_env(), info, answer(GameMove::Paper {})).unwrap();
+
+    let msg = ExecuteMsg::ExpireGame {
+        host: Addr::unchecked("host"),
+        opponent: Addr::unchecked("opponent"),
+    };
+    let mut env = mock_env();
+    env.block.height += 10;
+    let res = execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap();
+    assert_eq!(res.attributes[1].value, "Opponent won");
+    assert_eq!(query_balance(deps.as_ref(), "host"), vec![]);
+    assert_eq!(
+        query_balance(deps.as_ref(), "opponent"),
+        coins(100, "token")
+    );
+}
+
+#[test]
+fn stalled_series_forfeits() {
+    let mut deps = mock_dependencies();
+    let propose = ExecuteMsg::ProposeGame {
+        opponent: Addr::unchecked("opponent"),
+        terms: GameTerms {
+            wager: Some(coin(50, "token")),
+            ruleset: Ruleset::Classic {},
+            best_of: 3,
+            timeout: Some(Duration::Height(10)),
+        },
+    };
+    let expire = ExecuteMsg::ExpireGame {
+        host: Addr::unchecked("host"),
+        opponent: Addr::unchecked("opponent"),
+    };
+    let mut late = mock_env();
+    late.block.height += 10;
+
+    // host committed, the opponent never answers
+    let info = mock_info("host", &coins(50, "token"));
+    execute(deps.as_mut(), mock_env(), info, propose.clone()).unwrap();
+    let info = mock_info("opponent", &coins(50, "token"));
+    execute(deps.as_mut(), mock_env(), info, accept()).unwrap();
+    let info = mock_info("host", &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        commit(GameMove::Rock {}, "salt"),
+    )
+    .unwrap();
+    let info = mock_info("anyone", &[]);
+    let res = execute(deps.as_mut(), late.clone(), info, expire.clone()).unwrap();
+    assert!(res.attributes.contains(&attr("game_result", "Host won")));
+    assert_eq!(query_balance(deps.as_ref(), "host"), coins(100, "token"));
+    assert_eq!(query_balance(deps.as_ref(), "opponent"), vec![]);
+
+    // opponent accepted, the host never commits
+    execute(deps.as_mut(), mock_env(), mock_info("host", &[]), propose).unwrap();
+    let info = mock_info("opponent", &coins(50, "token"));
+    execute(deps.as_mut(), mock_env(), info, accept()).unwrap();
+    let info = mock_info("anyone", &[]);
+    let res = execute(deps.as_mut(), late, info, expire).unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("game_result", "Opponent won")));
+    assert_eq!(query_balance(deps.as_ref(), "host"), coins(50, "token"));
+    assert_eq!(
+        query_balance(deps.as_ref(), "opponent"),
+        coins(100, "token")
+    );
+}
+
+#[test]
+fn timeouts_are_bounded() {
+    let mut deps = mock_dependencies();
+    let msg = InstantiateMsg {
+        admin: Addr::unchecked("admin"),
+        allowlist_enabled: false,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+    let propose = |timeout: Duration| ExecuteMsg::ProposeGame {
+        opponent: Addr::unchecked("opponent"),
+        terms: GameTerms {
+            wager: None,
+            ruleset: Ruleset::Classic {},
+            best_of: 1,
+            timeout: Some(timeout),
+        },
+    };
+
+    // a game that expires at once, or never in practice, is rejected
+    for timeout in [
+        Duration::Height(0),
+        Duration::Height(9),
+        Duration::Time(59),
+        Duration::Height(100_801),
+        Duration::Time(604_801),
+    ] {
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            propose(timeout),
+        );
+        let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+        assert_eq!(*err_unwrapped, ContractError::InvalidTimeout {});
+    }
+    // same for the phases of a battle royale
+    let msg = ExecuteMsg::CreateRoyale {
+        entry_fee: None,
+        max_players: 3,
+        phase_timeout: Some(Duration::Height(1)),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::InvalidTimeout {});
+
+    // both limits are inclusive
+    let info = mock_info("host", &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        propose(Duration::Height(10)),
+    )
+    .unwrap();
+    let info = mock_info("host2", &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        propose(Duration::Time(604_800)),
+    )
+    .unwrap();
+
+    // the admin can move the limits, but not to an empty or zero range
+    let update = |min_blocks: u64, max_blocks: u64| ExecuteMsg::UpdateConfig {
+        allowlist_enabled: None,
+        game_bond: None,
+        max_open_games: None,
+        max_fee_bps: None,
+        treasury: None,
+        timeout_limits: Some(TimeoutLimits {
+            min_blocks,
+            max_blocks,
+            min_seconds: 60,
+            max_seconds: 3600,
+        }),
+    };
+    for (min, max) in [(0, 100), (50, 40)] {
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[]),
+            update(min, max),
+        );
+        let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+        assert_eq!(*err_unwrapped, ContractError::InvalidTimeout {});
+    }
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        update(50, 100),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::Admin(AdminError::NotAdmin {})
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        update(50, 100),
+    )
+    .unwrap();
+
+    let info = mock_info("host3", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        propose(Duration::Height(10)),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::InvalidTimeout {});
+    // the default timeout of a day is now too long as well
+    let msg = ExecuteMsg::ProposeGame {
+        opponent: Addr::unchecked("opponent"),
+        terms: GameTerms {
+            wager: None,
+            ruleset: Ruleset::Classic {},
+            best_of: 1,
+            timeout: None,
+        },
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("host3", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::InvalidTimeout {});
+    let info = mock_info("host3", &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        propose(Duration::Height(50)),
+    )
+    .unwrap();
+}
+
+#[test]
+fn challenge_terms_can_be_countered() {
+    let mut deps = mock_dependencies();
+    let host = mock_info("host", &[]);
+    let opponent = mock_info("opponent", &[]);
+    let terms = |amount: u128, best_of: u8| GameTerms {
+        wager: Some(coin(amount, "token")),
+        ruleset: Ruleset::Classic {},
+        best_of,
+        timeout: None,
+    };
+
+    let msg = ExecuteMsg::ProposeGame {
+        opponent: Addr::unchecked("opponent"),
+        terms: terms(100, 1),
+    };
+    let info = mock_info("host", &coins(100, "token"));
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // opponent wants a smaller wager, host rejects
+    let counter = |amount: u128, best_of: u8| ExecuteMsg::CounterChallenge {
+        host: Addr::unchecked("host"),
+        terms: terms(amount, best_of),
+    };
+    execute(deps.as_mut(), mock_env(), opponent.clone(), counter(20, 1)).unwrap();
+    // only one counter offer can be open at a time
+    let err = execute(deps.as_mut(), mock_env(), opponent.clone(), counter(30, 1));
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::WrongGameStatus {
+            status: GameStatus::Proposed
+        }
+    );
+    let msg = ExecuteMsg::RejectCounter {
+        opponent: Addr::unchecked("opponent"),
+    };
+    execute(deps.as_mut(), mock_env(), host.clone(), msg.clone()).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), host.clone(), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::NoCounterOffer {});
+
+    // second counter is accepted, host's escrow follows the new wager
+    execute(deps.as_mut(), mock_env(), opponent.clone(), counter(50, 3)).unwrap();
+    let msg = ExecuteMsg::AcceptCounter {
+        opponent: Addr::unchecked("opponent"),
+    };
+    execute(deps.as_mut(), mock_env(), host, msg).unwrap();
+    assert_eq!(query_balance(deps.as_ref(), "host"), coins(50, "token"));
+
+    let game = games()
+        .load(
+            &deps.storage,
+            (Addr::unchecked("host"), Addr::unchecked("opponent")),
+        )
+        .unwrap();
+    assert_eq!(game.wager, Some(coin(50, "token")));
+    assert_eq!(game.best_of, 3);
+    assert_eq!(
+        game.counter_offers,
+        vec![
+            CounterOffer {
+                terms: terms(20, 1),
+                status: CounterOfferStatus::Rejected
+            },
+            CounterOffer {
+                terms: terms(50, 3),
+                status: CounterOfferStatus::Accepted
+            }
+        ]
+    );
+
+    // negotiation is limited
+    execute(deps.as_mut(), mock_env(), opponent.clone(), counter(40, 3)).unwrap();
+    let msg = ExecuteMsg::RejectCounter {
+        opponent: Addr::unchecked("opponent"),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), opponent.clone(), counter(40, 3));
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::TooManyCounterOffers { max: 3 }
+    );
+
+    // opponent joins on the agreed terms
+    let msg = ExecuteMsg::AcceptChallenge {
+        host: Addr::unchecked("host"),
+    };
+    let info = mock_info("opponent", &coins(50, "token"));
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let game = games()
+        .load(
+            &deps.storage,
+            (Addr::unchecked("host"), Addr::unchecked("opponent")),
+        )
+        .unwrap();
+    assert_eq!(game.status, GameStatus::Accepted);
+    assert_eq!(game.counter_offers[2].status, CounterOfferStatus::Rejected);
+    let err = execute(deps.as_mut(), mock_env(), opponent, counter(40, 3));
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::WrongGameStatus {
+            status: GameStatus::Accepted
+        }
+    );
+}
+
+#[test]
+fn game_bond_and_open_games_cap() {
+    let mut deps = mock_dependencies();
+
+    let info = mock_info("creator", &[]);
+    let msg = InstantiateMsg {
+        admin: Addr::unchecked("admin"),
+        allowlist_enabled: false,
+    };
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let msg = ExecuteMsg::UpdateConfig {
+        allowlist_enabled: None,
+        game_bond: Some(coin(5, "token")),
+        max_open_games: Some(2),
+        max_fee_bps: None,
+        treasury: None,
+        timeout_limits: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+    // zero-stake games still need the bond
+    let start = |opponent: &str| ExecuteMsg::StartGame {
+        opponent: Addr::unchecked(opponent),
+        host_move: GameMove::Rock {},
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("bot", &[]),
+        start("alice"),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::InsufficientBalance {
+            denom: "token".to_string(),
+            available: Uint128::zero()
+        }
+    );
+
+    let info = mock_info("bot", &coins(20, "token"));
+    execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {}).unwrap();
+    let info = mock_info("bot", &[]);
+    execute(deps.as_mut(), mock_env(), info.clone(), start("alice")).unwrap();
+    execute(deps.as_mut(), mock_env(), info.clone(), start("bob")).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), info, start("carol"));
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::TooManyOpenGames { max: 2 });
+    assert_eq!(query_balance(deps.as_ref(), "bot"), coins(10, "token"));
+
+    // finished and declined games return the bond
+    let msg = ExecuteMsg::EndGame {
+        host: Addr::unchecked("bot"),
+        opponent_move: GameMove::Paper {},
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+    let msg = ExecuteMsg::DeclineChallenge {
+        host: Addr::unchecked("bot"),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), msg).unwrap();
+    assert_eq!(query_balance(deps.as_ref(), "bot"), coins(20, "token"));
+
+    // abandoned reveal forfeits the bond to the treasury
+    let msg = ExecuteMsg::ProposeGame {
+        opponent: Addr::unchecked("opponent"),
+        terms: GameTerms {
+            wager: None,
+            ruleset: Ruleset::Classic {},
+            best_of: 1,
+            timeout: Some(Duration::Height(10)),
+        },
+    };
+    let info = mock_info("host", &coins(5, "token"));
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let msg = ExecuteMsg::AcceptChallenge {
+        host: Addr::unchecked("host"),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("opponent", &[]), msg).unwrap();
+    let info = mock_info("host", &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        commit(GameMove::Rock {}, "salt"),
+    )
+    .unwrap();
+    let info = mock_info("opponent", &[]);
+    execute(deps.as_mut(), mock_env(), info, answer(GameMove::Paper {})).unwrap();
+
+    let msg = ExecuteMsg::ExpireGame {
+        host: Addr::unchecked("host"),
+        opponent: Addr::unchecked("opponent"),
+    };
+    let mut env = mock_env();
+    env.block.height += 10;
+    execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap();
+    assert_eq!(query_balance(deps.as_ref(), "host"), vec![]);
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::GetTreasury {}).unwrap();
+    let treasury: TreasuryResponse = from_binary(&res).unwrap();
+    assert_eq!(treasury.fees, coins(5, "token"));
+}
+
+// drand mainnet beacon, round 72785
+const DRAND_PUBKEY: &str = "868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31";
+const DRAND_GENESIS: u64 = 1595431050;
+const DRAND_ROUND: u64 = 72785;
+const DRAND_PREVIOUS_SIGNATURE: &str = "a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747";
+const DRAND_SIGNATURE: &str = "82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42";
+
+fn hex_binary(hex: &str) -> Binary {
+    Binary::from(hex::decode(hex).unwrap())
+}
+
+fn update_beacon(pubkey: Binary) -> ExecuteMsg {
+    ExecuteMsg::UpdateBeacon {
+        beacon: BeaconConfig {
+            pubkey,
+            genesis: Timestamp::from_seconds(DRAND_GENESIS),
+            period: 30,
+        },
+    }
+}
+
+fn submit_beacon(player: &str, round: u64, signature: &str) -> ExecuteMsg {
+    ExecuteMsg::SubmitBeacon {
+        player: Addr::unchecked(player),
+        round,
+        previous_signature: hex_binary(DRAND_PREVIOUS_SIGNATURE),
+        signature: hex_binary(signature),
+    }
+}
+
+#[test]
+fn drand_beacon_fixture() {
+    let pubkey = hex::decode(DRAND_PUBKEY).unwrap();
+    let previous_signature = hex::decode(DRAND_PREVIOUS_SIGNATURE).unwrap();
+    let signature = hex::decode(DRAND_SIGNATURE).unwrap();
+    assert!(verify_beacon(
+        &pubkey,
+        DRAND_ROUND,
+        &previous_signature,
+        &signature
+    ));
+    assert!(!verify_beacon(
+        &pubkey,
+        DRAND_ROUND + 1,
+        &previous_signature,
+        &signature
+    ));
+    assert!(!verify_beacon(
+        &pubkey,
+        DRAND_ROUND,
+        &previous_signature,
+        &previous_signature
+    ));
+    assert_eq!(
+        hex::encode(beacon_randomness(&signature)),
+        "8b676484b5fb1f37f9ec5c413d7d29883504e5b669f604a1ce68b3388e9ae3d9"
+    );
+
+    // round 72785 is published at genesis + 72784 * 30s
+    let genesis = Timestamp::from_seconds(DRAND_GENESIS);
+    let published = genesis.plus_seconds((DRAND_ROUND - 1) * 30);
+    assert_eq!(
+        next_round(genesis, 30, published.minus_seconds(1)),
+        DRAND_ROUND
+    );
+    assert_eq!(next_round(genesis, 30, published), DRAND_ROUND + 1);
+    assert_eq!(next_round(genesis, 30, genesis.minus_seconds(1)), 1);
+}
+
+#[test]
+fn player_against_the_house() {
+    let mut deps = mock_dependencies();
+
+    let info = mock_info("creator", &[]);
+    let msg = InstantiateMsg {
+        admin: Addr::unchecked("admin"),
+        allowlist_enabled: false,
+    };
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::PlayHouse {
+        player_move: GameMove::Scissors {},
+        wager: coin(100, "token"),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("player", &[]),
+        msg.clone(),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::HouseClosed {});
+
+    // 5% edge, wagers up to 10% of the bankroll
+    let house = HouseConfig {
+        open: true,
+        edge_bps: 500,
+        max_wager_bps: 1000,
+    };
+    let info = mock_info("admin", &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::UpdateHouse { house },
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("player", &[]),
+        msg.clone(),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::BeaconNotConfigured {});
+    let info = mock_info("admin", &[]);
+    let beacon = update_beacon(hex_binary(DRAND_PUBKEY));
+    execute(deps.as_mut(), mock_env(), info, beacon).unwrap();
+    let info = mock_info("player", &coins(1000, "token"));
+    let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::FundBankroll {});
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::Admin(AdminError::NotAdmin {})
+    );
+    let info = mock_info("admin", &coins(1000, "token"));
+    execute(deps.as_mut(), mock_env(), info, ExecuteMsg::FundBankroll {}).unwrap();
+
+    // last block before round 72785 is published
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(DRAND_GENESIS + (DRAND_ROUND - 2) * 30);
+
+    let big = ExecuteMsg::PlayHouse {
+        player_move: GameMove::Scissors {},
+        wager: coin(200, "token"),
+    };
+    let err = execute(deps.as_mut(), env.clone(), mock_info("player", &[]), big);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::WagerAboveHouseLimit {
+            max: Uint128::new(100)
+        }
+    );
+
+    // the beacon makes the house play paper against "player" and rock against "gambler"
+    let info = mock_info("player", &coins(100, "token"));
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap();
+    assert_eq!(res.attributes[2], attr("round", DRAND_ROUND.to_string()));
+    let err = execute(deps.as_mut(), env.clone(), mock_info("player", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::HouseGameInProgress {});
+
+    // house put up 95 for the first game, limit is now 90
+    let msg = ExecuteMsg::PlayHouse {
+        player_move: GameMove::Scissors {},
+        wager: coin(90, "token"),
+    };
+    let info = mock_info("gambler", &coins(90, "token"));
+    execute(deps.as_mut(), env, info, msg).unwrap();
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::GetHouseGame {
+            player: Addr::unchecked("gambler"),
+        },
+    )
+    .unwrap();
+    let game: Option<HouseGame> = from_binary(&res).unwrap();
+    assert_eq!(game.unwrap().reserved, Uint128::new(86));
+
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(1190, "token"));
+    assert_solvent(deps.as_ref(), &mock_env()).unwrap();
+
+    // a new key only applies to new games, open ones still settle with the key they started with
+    let msg = update_beacon(Binary::from(vec![0u8; 48]));
+    execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+    // only a valid beacon of the right round settles the game
+    let msg = submit_beacon("player", DRAND_ROUND - 1, DRAND_SIGNATURE);
+    let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::WrongBeaconRound {
+            expected: DRAND_ROUND
+        }
+    );
+    let msg = submit_beacon("player", DRAND_ROUND, DRAND_PREVIOUS_SIGNATURE);
+    let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::InvalidBeacon {});
+
+    let msg = submit_beacon("player", DRAND_ROUND, DRAND_SIGNATURE);
+    let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+    assert_eq!(res.attributes[2], attr("house_move", "paper"));
+    assert_eq!(res.attributes[3], attr("game_result", "Player won"));
+    let msg = submit_beacon("gambler", DRAND_ROUND, DRAND_SIGNATURE);
+    let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+    assert_eq!(res.attributes[3], attr("game_result", "House won"));
+
+    assert_eq!(query_balance(deps.as_ref(), "player"), coins(195, "token"));
+    assert_eq!(query_balance(deps.as_ref(), "gambler"), vec![]);
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::GetHouse {}).unwrap();
+    let house: HouseResponse = from_binary(&res).unwrap();
+    assert_eq!(house.bankroll, coins(995, "token"));
+    assert_solvent(deps.as_ref(), &mock_env()).unwrap();
+
+    // a game nobody settles is called off after a day
+    let msg = ExecuteMsg::PlayHouse {
+        player_move: GameMove::Rock {},
+        wager: coin(50, "token"),
+    };
+    let mut env = mock_env();
+    execute(deps.as_mut(), env.clone(), mock_info("player", &[]), msg).unwrap();
+    let msg = ExecuteMsg::ExpireHouseGame {
+        player: Addr::unchecked("player"),
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        msg.clone(),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::GameNotExpired {});
+
+    env.block.time = env.block.time.plus_seconds(24 * 60 * 60);
+    execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap();
+    assert_eq!(query_balance(deps.as_ref(), "player"), coins(195, "token"));
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::GetHouse {}).unwrap();
+    let house: HouseResponse = from_binary(&res).unwrap();
+    assert_eq!(house.bankroll, coins(995, "token"));
+}
+
+// what the chain hands back from a wasm execute submessage: the bot's data wrapped
+// in a protobuf MsgExecuteContractResponse
+fn bot_answer(id: u64, bot_move: BotMove) -> Reply {
+    let data = to_binary(&BotMoveResponse { bot_move }).unwrap();
+    let mut wrapped = vec![0x0a, data.len() as u8];
+    wrapped.extend_from_slice(&data);
+    Reply {
+        id,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(Binary::from(wrapped)),
+        }),
+    }
+}
+
+fn challenge_beacon(host: &str, opponent: &str) -> ExecuteMsg {
+    ExecuteMsg::SubmitChallengeBeacon {
+        host: Addr::unchecked(host),
+        opponent: Addr::unchecked(opponent),
+        round: DRAND_ROUND,
+        previous_signature: hex_binary(DRAND_PREVIOUS_SIGNATURE),
+        signature: hex_binary(DRAND_SIGNATURE),
+    }
+}
+
+#[test]
+fn registered_bot_answers_challenge() {
+    let mut deps = mock_dependencies();
+
+    let info = mock_info("creator", &[]);
+    let msg = InstantiateMsg {
+        admin: Addr::unchecked("admin"),
+        allowlist_enabled: false,
+    };
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let msg = ExecuteMsg::RegisterBot {
+        addr: Addr::unchecked("bot"),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+    let msg = ExecuteMsg::RemoveBot {
+        addr: Addr::unchecked("human"),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::NotABot {
+            addr: Addr::unchecked("human")
+        }
+    );
+    let msg = QueryMsg::GetBots {
+        start_after: None,
+        limit: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let bots: BotsResponse = from_binary(&res).unwrap();
+    assert_eq!(bots.bots, vec![Addr::unchecked("bot")]);
+
+    // the bot plays from its own balance
+    let info = mock_info("bot", &coins(50, "token"));
+    execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {}).unwrap();
+
+    // a plain StartGame would show the host move to the bot
+    let start = ExecuteMsg::StartGame {
+        opponent: Addr::unchecked("bot"),
+        host_move: GameMove::Rock {},
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("host", &[]), start);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::BotNeedsCommitment {});
+
+    let challenge = |wager: Option<Coin>| ExecuteMsg::CommitChallenge {
+        opponent: Addr::unchecked("bot"),
+        commitment: move_commitment(&GameMove::Rock {}, "salt"),
+        wager,
+    };
+    let info = mock_info("host", &coins(20, "token"));
+    let err = execute(deps.as_mut(), mock_env(), info, challenge(None));
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::BeaconNotConfigured {});
+
+    // bots don't need house mode, only the beacon
+    let msg = update_beacon(Binary::from(vec![0u8; 32]));
+    let err = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::InvalidBeaconConfig {});
+    let msg = update_beacon(hex_binary(DRAND_PUBKEY));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &[]),
+        msg.clone(),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::Admin(AdminError::NotAdmin {})
+    );
+    execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::GetBeacon {}).unwrap();
+    let beacon: Option<BeaconConfig> = from_binary(&res).unwrap();
+    assert_eq!(beacon.unwrap().pubkey, hex_binary(DRAND_PUBKEY));
+
+    // last block before round 72785 is published
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(DRAND_GENESIS + (DRAND_ROUND - 2) * 30);
+    let info = mock_info("host", &coins(20, "token"));
+    let wager = Some(coin(10, "token"));
+    let res = execute(deps.as_mut(), env.clone(), info, challenge(wager.clone())).unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("round", DRAND_ROUND.to_string())));
+    assert_eq!(query_balance(deps.as_ref(), "bot"), coins(40, "token"));
+
+    // the bot is only asked once the beacon is in
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        challenge_beacon("host", "bot"),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    let bot_challenge = BotChallengeMsg {
+        host: Addr::unchecked("host"),
+        wager,
+        randomness: Binary::from(
+            beacon_randomness(&hex::decode(DRAND_SIGNATURE).unwrap()).to_vec(),
+        ),
+    };
+    assert_eq!(
+        res.messages[0].msg,
+        bot_challenge.into_cosmos_msg("bot").unwrap()
+    );
+
+    let res = reply(
+        deps.as_mut(),
+        env.clone(),
+        bot_answer(res.messages[0].id, BotMove::Paper {}),
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("status", "awaiting_reveal")));
+    assert!(res.attributes.contains(&attr("bot", "bot")));
+    let msg = ExecuteMsg::RevealMove {
+        opponent: Addr::unchecked("bot"),
+        host_move: GameMove::Rock {},
+        salt: "salt".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("host", &[]), msg).unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("game_result", "Opponent won")));
+    assert_eq!(query_balance(deps.as_ref(), "host"), coins(10, "token"));
+    assert_eq!(query_balance(deps.as_ref(), "bot"), coins(60, "token"));
+
+    // a bot that doesn't answer with a move calls the game off
+    let info = mock_info("host", &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        challenge(Some(coin(10, "token"))),
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        challenge_beacon("host", "bot"),
+    )
+    .unwrap();
+    let no_move = Reply {
+        id: res.messages[0].id,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let res = reply(deps.as_mut(), env.clone(), no_move).unwrap();
+    assert!(res.attributes.contains(&attr("bot_failed", "bot")));
+    assert_eq!(query_balance(deps.as_ref(), "host"), coins(10, "token"));
+    assert_eq!(query_balance(deps.as_ref(), "bot"), coins(60, "token"));
+
+    // nobody submitted the beacon, the host could be holding back a lost game
+    let info = mock_info("host", &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        challenge(Some(coin(10, "token"))),
+    )
+    .unwrap();
+    env.block.time = env.block.time.plus_seconds(24 * 60 * 60);
+    let msg = ExecuteMsg::ExpireGame {
+        host: Addr::unchecked("host"),
+        opponent: Addr::unchecked("bot"),
+    };
+    let res = execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("game_result", "Opponent won")));
+    assert_eq!(query_balance(deps.as_ref(), "host"), vec![]);
+    assert_eq!(query_balance(deps.as_ref(), "bot"), coins(70, "token"));
+}
+
+#[test]
+fn standing_strategy_answers_offline() {
+    let mut deps = mock_dependencies();
+
+    let info = mock_info("creator", &[]);
+    let msg = InstantiateMsg {
+        admin: Addr::unchecked("admin"),
+        allowlist_enabled: false,
+    };
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let msg = update_beacon(hex_binary(DRAND_PUBKEY));
+    execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+    // a move that's never played would leave the host a move that can't lose
+    let msg = ExecuteMsg::SetStrategy {
+        strategy: Strategy::Weighted {
+            rock: 0,
+            paper: 1,
+            scissors: 1,
+        },
+        max_wager: None,
+        max_answers: 10,
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::InvalidStrategy {});
+
+    let msg = ExecuteMsg::SetStrategy {
+        strategy: Strategy::Weighted {
+            rock: 1,
+            paper: 1,
+            scissors: 1,
+        },
+        max_wager: Some(coin(10, "token")),
+        max_answers: 2,
+    };
+    let info = mock_info("alice", &coins(20, "token"));
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // last block before round 72785 is published
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(DRAND_GENESIS + (DRAND_ROUND - 2) * 30);
+    let challenge = |opponent: &str, wager: Option<Coin>| ExecuteMsg::CommitChallenge {
+        opponent: Addr::unchecked(opponent),
+        commitment: move_commitment(&GameMove::Paper {}, "salt"),
+        wager,
+    };
+    let reveal = |opponent: &str| ExecuteMsg::RevealMove {
+        opponent: Addr::unchecked(opponent),
+        host_move: GameMove::Paper {},
+        salt: "salt".to_string(),
+    };
+    let info = mock_info("host", &coins(30, "token"));
+    let msg = challenge("alice", Some(coin(5, "token")));
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        challenge_beacon("host", "alice"),
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("standing_strategy", "true")));
+    assert!(res.attributes.contains(&attr("status", "awaiting_reveal")));
+    let info = mock_info("host", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, reveal("alice")).unwrap();
+    assert!(res.attributes.contains(&attr("game_result", "Tie")));
+
+    // above the limit alice has to answer by hand
+    let info = mock_info("host", &[]);
+    let msg = challenge("alice", Some(coin(15, "token")));
+    let err = execute(deps.as_mut(), env.clone(), info, msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::NoAutoAnswer {
+            addr: Addr::unchecked("alice")
+        }
+    );
+
+    // same round, but the next answer is drawn anew
+    let info = mock_info("host", &[]);
+    let msg = challenge("alice", Some(coin(5, "token")));
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let msg = challenge_beacon("host", "alice");
+    execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg).unwrap();
+    let info = mock_info("host", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, reveal("alice")).unwrap();
+    assert!(res.attributes.contains(&attr("game_result", "Host won")));
+    assert_eq!(query_balance(deps.as_ref(), "alice"), coins(15, "token"));
+
+    // max_answers caps what the strategy can lose
+    let info = mock_info("host", &[]);
+    let msg = challenge("alice", Some(coin(5, "token")));
+    let err = execute(deps.as_mut(), env.clone(), info, msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::NoAutoAnswer {
+            addr: Addr::unchecked("alice")
+        }
+    );
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::GetStrategy {
+            player: Addr::unchecked("alice"),
+        },
+    )
+    .unwrap();
+    let standing: Option<StandingStrategy> = from_binary(&res).unwrap();
+    assert_eq!(standing.unwrap().answered, 2);
+
+    // the host is part of the pick, another host on the same round gets another answer
+    let msg = ExecuteMsg::SetStrategy {
+        strategy: Strategy::Weighted {
+            rock: 1,
+            paper: 1,
+            scissors: 1,
+        },
+        max_wager: None,
+        max_answers: 10,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), msg).unwrap();
+    let msg = challenge("bob", None);
+    execute(deps.as_mut(), env.clone(), mock_info("carol", &[]), msg).unwrap();
+    let msg = challenge_beacon("carol", "bob");
+    execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg).unwrap();
+    let res = execute(deps.as_mut(), env, mock_info("carol", &[]), reveal("bob")).unwrap();
+    assert!(res.attributes.contains(&attr("game_result", "Host won")));
+
+    let clear = ExecuteMsg::ClearStrategy {};
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("bob", &[]),
+        clear.clone(),
+    )
+    .unwrap();
+    let err = execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), clear);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::NoStrategy {});
+}
+
+fn signed_payload(nonce: u64, wager: Option<Coin>, expires: Expiration) -> Vec<u8> {
+    SignedMovePayload {
+        domain: SIGNED_MOVE_DOMAIN.to_string(),
+        contract: Addr::unchecked(MOCK_CONTRACT_ADDR),
+        host: Addr::unchecked("host"),
+        opponent: Addr::unchecked("opponent"),
+        nonce,
+        wager,
+        host_commitment: move_commitment(&GameMove::Rock {}, "host salt"),
+        opponent_commitment: move_commitment(&GameMove::Scissors {}, "opponent salt"),
+        expires,
+    }
+    .to_bytes()
+    .unwrap()
+}
+
+#[test]
+fn signed_moves_played_in_one_transaction() {
+    let mut deps = mock_dependencies();
+
+    // host signs with secp256k1, opponent with ed25519
+    let host_key = k256::ecdsa::SigningKey::from_bytes(&[1u8; 32]).unwrap();
+    let host_pubkey = host_key.verifying_key().to_bytes().to_vec();
+    let opponent_key = ed25519_zebra::SigningKey::from([2u8; 32]);
+    let opponent_pubkey: [u8; 32] = ed25519_zebra::VerificationKey::from(&opponent_key).into();
+    let host_sign = |payload: &[u8]| -> Binary {
+        let signature: k256::ecdsa::Signature = host_key.sign(payload);
+        Binary::from(signature.as_ref())
+    };
+    let opponent_sign = |payload: &[u8]| -> Binary {
+        let signature: [u8; 64] = opponent_key.sign(payload).into();
+        Binary::from(signature.to_vec())
+    };
+
+    let msg = ExecuteMsg::SetSigningKey {
+        key_type: KeyType::Ed25519,
+        pubkey: Binary::from(host_pubkey.clone()),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::InvalidSigningKey {});
+
+    let msg = ExecuteMsg::SetSigningKey {
+        key_type: KeyType::Secp256k1,
+        pubkey: Binary::from(host_pubkey),
+    };
+    let info = mock_info("host", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("host", &coins(10, "token")),
+        ExecuteMsg::Deposit {},
+    )
+    .unwrap();
+    let msg = ExecuteMsg::SetSigningKey {
+        key_type: KeyType::Ed25519,
+        pubkey: Binary::from(opponent_pubkey.to_vec()),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("opponent", &[]), msg).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("opponent", &coins(10, "token")),
+        ExecuteMsg::Deposit {},
+    )
+    .unwrap();
+
+    // both players sign the same payload with both commitments, then reveal in PlaySigned
+    let wager = Some(coin(10, "token"));
+    let expires = Expiration::AtHeight(mock_env().block.height + 10);
+    let payload = signed_payload(0, wager.clone(), expires);
+    let host_move = SignedMove {
+        game_move: GameMove::Rock {},
+        salt: "host salt".to_string(),
+        signature: host_sign(&payload),
+    };
+    let opponent_move = SignedMove {
+        game_move: GameMove::Scissors {},
+        salt: "opponent salt".to_string(),
+        signature: opponent_sign(&payload),
+    };
+    let play = |host_move: &SignedMove, opponent_move: &SignedMove, wager: &Option<Coin>| {
+        ExecuteMsg::PlaySigned {
+            host: Addr::unchecked("host"),
+            opponent: Addr::unchecked("opponent"),
+            wager: wager.clone(),
+            expires,
+            host_move: host_move.clone(),
+            opponent_move: opponent_move.clone(),
+        }
+    };
+
+    // the signatures cover the wager and the committed moves
+    let msg = play(&host_move, &opponent_move, &Some(coin(5, "token")));
+    let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::InvalidSignature {
+            addr: Addr::unchecked("host")
+        }
+    );
+    let forged = SignedMove {
+        game_move: GameMove::Paper {},
+        ..opponent_move.clone()
+    };
+    let msg = play(&host_move, &forged, &wager);
+    let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::InvalidSignature {
+            addr: Addr::unchecked("host")
+        }
+    );
+
+    // the signatures can't be played once they expired
+    let mut late = mock_env();
+    late.block.height += 10;
+    let msg = play(&host_move, &opponent_move, &wager);
+    let err = execute(deps.as_mut(), late, mock_info("relayer", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::GameExpired {});
+
+    let msg = play(&host_move, &opponent_move, &wager);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("relayer", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("game_result", "Host won")));
+    assert_eq!(query_balance(deps.as_ref(), "host"), coins(20, "token"));
+    assert_eq!(query_balance(deps.as_ref(), "opponent"), vec![]);
+
+    // replaying the same signatures doesn't work, the next game has nonce 1
+    let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::InvalidSignature {
+            addr: Addr::unchecked("host")
+        }
+    );
+    let msg = QueryMsg::GetSignedGameNonce {
+        host: Addr::unchecked("host"),
+        opponent: Addr::unchecked("opponent"),
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    assert_eq!(from_binary::<u64>(&res).unwrap(), 1);
+}
+
+fn relay(env: &Env, key: &k256::ecdsa::SigningKey, nonce: u64, msg: ExecuteMsg) -> ExecuteMsg {
+    let payload = to_binary(&RelayedMsg {
+        domain: RELAYED_MSG_DOMAIN.to_string(),
+        chain_id: env.block.chain_id.clone(),
+        contract: env.contract.address.clone(),
+        nonce,
+        msg,
+    })
+    .unwrap();
+    let signature: k256::ecdsa::Signature = key.sign(payload.as_slice());
+    ExecuteMsg::ExecuteSigned {
+        payload,
+        signature: Binary::from(signature.as_ref()),
+        pubkey: Binary::from(key.verifying_key().to_bytes().to_vec()),
+    }
+}
+
+#[test]
+fn relayed_messages_run_for_signer() {
+    // cosmjs test vector
+    let pubkey = Binary::from_base64("AtQaCqFnshaZQp6rIkvAPyzThvCvXSDO+9AzbxVErqJP").unwrap();
+    assert_eq!(
+        derive_address("cosmos", &pubkey).unwrap(),
+        "cosmos1h806c7khnvmjlywdrkdgk2vrayy2mmvf9rxk2r"
+    );
+
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    env.contract.address = Addr::unchecked("cosmos1h806c7khnvmjlywdrkdgk2vrayy2mmvf9rxk2r");
+
+    let host_key = k256::ecdsa::SigningKey::from_bytes(&[3u8; 32]).unwrap();
+    let host_pubkey = host_key.verifying_key().to_bytes();
+    let host = derive_address("cosmos", &host_pubkey).unwrap();
+    let opponent_key = k256::ecdsa::SigningKey::from_bytes(&[4u8; 32]).unwrap();
+    let opponent_pubkey = opponent_key.verifying_key().to_bytes();
+    let opponent = derive_address("cosmos", &opponent_pubkey).unwrap();
+
+    let start = ExecuteMsg::StartGame {
+        opponent: Addr::unchecked(&opponent),
+        host_move: GameMove::Scissors {},
+    };
+    let msg = relay(&env, &host_key, 0, start);
+
+    // host's signature doesn't run anything for the opponent, nor can the relayer pay along
+    let tampered = match msg.clone() {
+        ExecuteMsg::ExecuteSigned {
+            payload, signature, ..
+        } => ExecuteMsg::ExecuteSigned {
+            payload,
+            signature,
+            pubkey: Binary::from(opponent_pubkey.to_vec()),
+        },
+        _ => unreachable!(),
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("relayer", &[]),
+        tampered,
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::InvalidSignature {
+            addr: Addr::unchecked(&opponent)
+        }
+    );
+    let info = mock_info("relayer", &coins(10, "token"));
+    let err = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::Payment(PaymentError::NonPayable {})
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("relayer", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("relayed_for", &host)));
+    let err = execute(deps.as_mut(), env.clone(), mock_info("relayer", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::WrongNonce { expected: 1 });
+
+    // signed for another contract
+    let end = ExecuteMsg::EndGame {
+        host: Addr::unchecked(&host),
+        opponent_move: GameMove::Rock {},
+    };
+    let msg = relay(&mock_env(), &opponent_key, 0, end.clone());
+    let err = execute(deps.as_mut(), env.clone(), mock_info("relayer", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::InvalidRelayedMsg {});
+
+    let msg = relay(&env, &opponent_key, 0, end);
+    let res = execute(deps.as_mut(), env.clone(), mock_info("relayer", &[]), msg).unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("game_result", "Opponent won")));
+    assert!(res.attributes.contains(&attr("relayed_for", &opponent)));
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::GetRelayNonce {
+            player: Addr::unchecked(&opponent),
+        },
+    )
+    .unwrap();
+    assert_eq!(from_binary::<u64>(&res).unwrap(), 1);
+}
+
+#[test]
+fn operator_plays_for_player() {
+    let mut deps = mock_dependencies();
+
+    let info = mock_info("cold", &coins(100, "token"));
+    execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {}).unwrap();
+    let expires = Expiration::AtHeight(mock_env().block.height + 100);
+    let msg = ExecuteMsg::ApproveOperator {
+        operator: Addr::unchecked("hot"),
+        max_wager: Some(coin(20, "token")),
+        expires: Some(expires),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("cold", &[]), msg).unwrap();
+
+    let as_cold = |msg: ExecuteMsg| ExecuteMsg::ExecuteAs {
+        player: Addr::unchecked("cold"),
+        msg: Box::new(msg),
+    };
+    let start = |wager: u128| propose(Some(coin(wager, "token")));
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("stranger", &[]),
+        as_cold(start(20)),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::NotOperator {
+            player: Addr::unchecked("cold")
+        }
+    );
+    let info = mock_info("hot", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, as_cold(start(30)));
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::WagerAboveOperatorLimit {
+            max: coin(20, "token")
+        }
+    );
+    let withdraw = ExecuteMsg::Withdraw {
+        amount: coin(100, "token"),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("hot", &[]),
+        as_cold(withdraw.clone()),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::NotOperatorAction {});
+    // a plain game would show cold's move
+    let msg = ExecuteMsg::StartGame {
+        opponent: Addr::unchecked("alice"),
+        host_move: GameMove::Rock {},
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("hot", &[]),
+        as_cold(msg),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::NotOperatorAction {});
+    // funds sent along with messages that don't take a deposit would be stuck in the contract
+    let block = ExecuteMsg::BlockPlayer {
+        addr: Addr::unchecked("alice"),
+    };
+    let approve = ExecuteMsg::ApproveOperator {
+        operator: Addr::unchecked("hot"),
+        max_wager: None,
+        expires: None,
+    };
+    for msg in [withdraw, block, approve] {
+        let info = mock_info("cold", &coins(5, "token"));
+        let err = execute(deps.as_mut(), mock_env(), info, msg);
+        let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+        assert_eq!(
+            *err_unwrapped,
+            ContractError::Payment(PaymentError::NonPayable {})
+        );
+    }
+
+    // hot wallet starts a game and answers another one, stakes come from cold's balance
+    let info = mock_info("hot", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, as_cold(start(20))).unwrap();
+    assert!(res.attributes.contains(&attr("operator", "hot")));
+    let msg = ExecuteMsg::ProposeGame {
+        opponent: Addr::unchecked("cold"),
+        terms: GameTerms {
+            wager: Some(coin(10, "token")),
+            ruleset: Ruleset::Classic {},
+            best_of: 1,
+            timeout: None,
+        },
+    };
+    let info = mock_info("host", &coins(10, "token"));
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let info = mock_info("hot", &[]);
+    execute(deps.as_mut(), mock_env(), info.clone(), as_cold(accept())).unwrap();
+    let msg = ExecuteMsg::CommitMove {
+        opponent: Addr::unchecked("cold"),
+        commitment: move_commitment(&GameMove::Paper {}, "salt"),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg).unwrap();
+    let msg = as_cold(answer(GameMove::Scissors {}));
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let msg = ExecuteMsg::RevealMove {
+        opponent: Addr::unchecked("cold"),
+        host_move: GameMove::Paper {},
+        salt: "salt".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg).unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("game_result", "Opponent won")));
+    assert_eq!(query_balance(deps.as_ref(), "cold"), coins(90, "token"));
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::GetGamesByHost {
+            host: Addr::unchecked("cold"),
+        },
+    )
+    .unwrap();
+    let games: GamesListResponse = from_binary(&res).unwrap();
+    assert_eq!(games.games.len(), 1);
+
+    // the approval lapses on its own
+    let mut env = mock_env();
+    env.block.height += 100;
+    let msg = ExecuteMsg::StartGame {
+        opponent: Addr::unchecked("alice"),
+        host_move: GameMove::Rock {},
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("hot", &[]),
+        as_cold(msg),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::NotOperator {
+            player: Addr::unchecked("cold")
+        }
+    );
+    let msg = QueryMsg::GetOperators {
+        player: Addr::unchecked("cold"),
+        start_after: None,
+        limit: None,
+    };
+    let res = query(deps.as_ref(), env, msg).unwrap();
+    let operators: OperatorsResponse = from_binary(&res).unwrap();
+    assert_eq!(operators.operators.len(), 1);
+    assert!(!operators.operators[0].active);
+
+    let msg = ExecuteMsg::RevokeOperator {
+        operator: Addr::unchecked("hot"),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("cold", &[]), msg).unwrap();
+}
+
+#[test]
+fn battle_royale_last_survivor_takes_pot() {
+    let mut deps = mock_dependencies();
+
+    let msg = ExecuteMsg::CreateRoyale {
+        entry_fee: Some(coin(10, "token")),
+        max_players: 4,
+        phase_timeout: Some(Duration::Height(10)),
+    };
+    let info = mock_info("alice", &coins(10, "token"));
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let join = ExecuteMsg::JoinRoyale { id: 1 };
+    for player in ["bob", "carol"] {
+        let info = mock_info(player, &coins(10, "token"));
+        execute(deps.as_mut(), mock_env(), info, join.clone()).unwrap();
+    }
+    let info = mock_info("bob", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, join.clone());
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::AlreadyJoined {});
+
+    // the fourth player fills it up and the first round starts
+    let info = mock_info("dave", &coins(10, "token"));
+    execute(deps.as_mut(), mock_env(), info, join.clone()).unwrap();
+    let info = mock_info("erin", &coins(10, "token"));
+    let err = execute(deps.as_mut(), mock_env(), info, join);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::WrongRoyaleStatus {
+            status: RoyaleStatus::Commit
+        }
+    );
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::GetRoyale { id: 1 }).unwrap();
+    let royale: Option<Royale> = from_binary(&res).unwrap();
+    let royale = royale.unwrap();
+    assert_eq!(royale.pot, Uint128::new(40));
+    assert_eq!(royale.round, 1);
+    assert_eq!(query_balance(deps.as_ref(), "alice"), vec![]);
+
+    // dave never commits and forfeits once the phase times out
+    let commit_royale = |game_move: GameMove, salt: &str| ExecuteMsg::CommitRoyaleMove {
+        id: 1,
+        commitment: move_commitment(&game_move, salt),
+    };
+    let reveal_royale = |game_move: GameMove, salt: &str| ExecuteMsg::RevealRoyaleMove {
+        id: 1,
+        game_move,
+        salt: salt.to_string(),
+    };
+    for (player, game_move) in [
+        ("alice", GameMove::Rock {}),
+        ("bob", GameMove::Rock {}),
+        ("carol", GameMove::Scissors {}),
+    ] {
+        let info = mock_info(player, &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            commit_royale(game_move, player),
+        )
+        .unwrap();
+    }
+    let advance = ExecuteMsg::AdvanceRoyale { id: 1 };
+    let info = mock_info("alice", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, advance.clone());
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::GameNotExpired {});
+    let mut env = mock_env();
+    env.block.height += 11;
+    let info = mock_info("alice", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, advance).unwrap();
+    assert!(res.attributes.contains(&attr("players", "3")));
+
+    // rock beats scissors, carol is out
+    for (player, game_move) in [
+        ("alice", GameMove::Rock {}),
+        ("bob", GameMove::Rock {}),
+        ("carol", GameMove::Scissors {}),
+    ] {
+        let info = mock_info(player, &[]);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            reveal_royale(game_move, player),
+        )
+        .unwrap();
+    }
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::GetRoyale { id: 1 }).unwrap();
+    let royale: Option<Royale> = from_binary(&res).unwrap();
+    let royale = royale.unwrap();
+    assert_eq!(
+        royale.players,
+        vec![Addr::unchecked("alice"), Addr::unchecked("bob")]
+    );
+    assert_eq!(royale.round, 2);
+
+    // a tampered reveal is rejected
+    let info = mock_info("alice", &[]);
+    let msg = commit_royale(GameMove::Rock {}, "alice2");
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let info = mock_info("bob", &[]);
+    let msg = commit_royale(GameMove::Paper {}, "bob2");
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let info = mock_info("alice", &[]);
+    let msg = reveal_royale(GameMove::Paper {}, "alice2");
+    let err = execute(deps.as_mut(), env.clone(), info, msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::InvalidCommitment {});
+
+    let info = mock_info("alice", &[]);
+    let msg = reveal_royale(GameMove::Rock {}, "alice2");
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let info = mock_info("bob", &[]);
+    let msg = reveal_royale(GameMove::Paper {}, "bob2");
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert!(res.attributes.contains(&attr("royale_winner", "bob")));
+    assert_eq!(query_balance(deps.as_ref(), "bob"), coins(40, "token"));
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::GetRoyale { id: 1 }).unwrap();
+    let royale: Option<Royale> = from_binary(&res).unwrap();
+    assert_eq!(royale, None);
+}
+
+#[test]
+fn team_game_plays_majority_vote() {
+    let mut deps = mock_dependencies();
+
+    let team = |members: &[&str]| members.iter().map(|m| Addr::unchecked(*m)).collect();
+    let create = |opponent_team: Vec<Addr>| ExecuteMsg::CreateTeamGame {
+        host_team: team(&["ann", "amy", "abe"]),
+        opponent_team,
+        wager: Some(coin(10, "token")),
+        tie_break: TieBreak::Captain {},
+        phase_timeout: Some(Duration::Height(10)),
+    };
+    let info = mock_info("ann", &[]);
+    let msg = create(team(&["bea", "ann", "bob"]));
+    let err = execute(deps.as_mut(), mock_env(), info, msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::InvalidTeams { max: MAX_TEAM_SIZE }
+    );
+    let info = mock_info("bea", &[]);
+    let msg = create(team(&["bea", "ben", "bob"]));
+    let err = execute(deps.as_mut(), mock_env(), info, msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::NotInTeam {});
+    let info = mock_info("ann", &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        create(team(&["bea", "ben", "bob"])),
+    )
+    .unwrap();
+
+    // host team splits three ways, the captain's rock counts
+    // bob never reveals, so the opponent team forfeits even though its majority is scissors
+    let votes = [
+        ("ann", GameMove::Rock {}),
+        ("amy", GameMove::Paper {}),
+        ("abe", GameMove::Scissors {}),
+        ("bea", GameMove::Scissors {}),
+        ("ben", GameMove::Scissors {}),
+        ("bob", GameMove::Paper {}),
+    ];
+    for (member, vote) in votes.iter() {
+        let msg = ExecuteMsg::CommitTeamVote {
+            id: 1,
+            commitment: move_commitment(vote, member),
+        };
+        let info = mock_info(member, &coins(10, "token"));
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::GetTeamGame { id: 1 }).unwrap();
+    let team_game: Option<TeamGame> = from_binary(&res).unwrap();
+    let team_game = team_game.unwrap();
+    assert_eq!(team_game.status, TeamGameStatus::Reveal);
+    assert_eq!(team_game.pot, Uint128::new(60));
+
+    for (member, vote) in votes.iter().filter(|(member, _)| *member != "bob") {
+        let msg = ExecuteMsg::RevealTeamVote {
+            id: 1,
+            game_move: vote.clone(),
+            salt: member.to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(member, &[]), msg).unwrap();
+    }
+    let advance = ExecuteMsg::AdvanceTeamGame { id: 1 };
+    let info = mock_info("bob", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, advance.clone());
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::GameNotExpired {});
+    let mut env = mock_env();
+    env.block.height += 11;
+    let res = execute(deps.as_mut(), env, mock_info("bob", &[]), advance).unwrap();
+    assert!(res.attributes.contains(&attr("host_team_move", "rock")));
+    assert!(!res.attributes.iter().any(|a| a.key == "opponent_team_move"));
+    assert!(res.attributes.contains(&attr("result", "Host won")));
+
+    // the winning team shares the pot
+    for member in ["ann", "amy", "abe"] {
+        assert_eq!(query_balance(deps.as_ref(), member), coins(20, "token"));
+    }
+    for member in ["bea", "ben", "bob"] {
+        assert_eq!(query_balance(deps.as_ref(), member), vec![]);
+    }
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::GetTeamGame { id: 1 }).unwrap();
+    let team_game: Option<TeamGame> = from_binary(&res).unwrap();
+    assert_eq!(team_game, None);
+
+    // both teams forfeit, only the members who revealed get their stake back
+    let msg = ExecuteMsg::CreateTeamGame {
+        host_team: team(&["cat", "cyd"]),
+        opponent_team: team(&["dan", "dee"]),
+        wager: Some(coin(10, "token")),
+        tie_break: TieBreak::Captain {},
+        phase_timeout: Some(Duration::Height(10)),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("cat", &[]), msg).unwrap();
+    for member in ["cat", "cyd", "dan", "dee"] {
+        let msg = ExecuteMsg::CommitTeamVote {
+            id: 2,
+            commitment: move_commitment(&GameMove::Rock {}, member),
+        };
+        let info = mock_info(member, &coins(10, "token"));
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+    for member in ["cat", "dan"] {
+        let msg = ExecuteMsg::RevealTeamVote {
+            id: 2,
+            game_move: GameMove::Rock {},
+            salt: member.to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(member, &[]), msg).unwrap();
+    }
+    let mut env = mock_env();
+    env.block.height += 11;
+    let advance = ExecuteMsg::AdvanceTeamGame { id: 2 };
+    let res = execute(deps.as_mut(), env, mock_info("cat", &[]), advance).unwrap();
+    assert!(res.attributes.contains(&attr("result", "Tie")));
+    for member in ["cat", "dan"] {
+        assert_eq!(query_balance(deps.as_ref(), member), coins(10, "token"));
+    }
+    for member in ["cyd", "dee"] {
+        assert_eq!(query_balance(deps.as_ref(), member), vec![]);
+    }
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::GetTreasury {}).unwrap();
+    let treasury: TreasuryResponse = from_binary(&res).unwrap();
+    assert_eq!(treasury.fees, coins(20, "token"));
+}
+
+#[test]
+fn limited_hand_uses_up_moves() {
+    let mut deps = mock_dependencies();
+    let host = mock_info("host", &[]);
+    let opponent = mock_info("opponent", &[]);
+
+    let hand = |rock, paper, scissors| Hand {
+        rock,
+        paper,
+        scissors,
+    };
+    let propose = |hand: Hand| ExecuteMsg::ProposeGame {
+        opponent: Addr::unchecked("opponent"),
+        terms: GameTerms {
+            wager: Some(coin(50, "token")),
+            ruleset: Ruleset::LimitedHand { hand },
+            best_of: 3,
+            timeout: None,
+        },
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        host.clone(),
+        propose(hand(1, 0, 1)),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::InvalidHand {});
+    let info = mock_info("host", &coins(50, "token"));
+    execute(deps.as_mut(), mock_env(), info, propose(hand(1, 1, 1))).unwrap();
+    let msg = ExecuteMsg::AcceptChallenge {
+        host: Addr::unchecked("host"),
+    };
+    let info = mock_info("opponent", &coins(50, "token"));
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // round 1: both play their only rock
+    let play = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, host_move: GameMove, opponent_move| {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            commit(host_move.clone(), "salt"),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("opponent", &[]),
+            answer(opponent_move),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("host", &[]),
+            reveal(host_move, "salt"),
+        )
+        .unwrap()
+    };
+    let res = play(&mut deps, GameMove::Rock {}, GameMove::Rock {});
+    assert_eq!(res.attributes[1].value, "Tie");
+    let game = games()
+        .load(
+            &deps.storage,
+            (Addr::unchecked("host"), Addr::unchecked("opponent")),
+        )
+        .unwrap();
+    assert_eq!(game.host_hand, Some(hand(0, 1, 1)));
+    assert_eq!(game.opponent_hand, Some(hand(0, 1, 1)));
+
+    // rock is used up
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        host.clone(),
+        commit(GameMove::Paper {}, "salt"),
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        opponent.clone(),
+        answer(GameMove::Rock {}),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::MoveExhausted {
+            game_move: GameMove::Rock {}
+        }
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        opponent,
+        answer(GameMove::Scissors {}),
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        host,
+        reveal(GameMove::Paper {}, "salt"),
+    )
+    .unwrap();
+    assert_eq!(res.attributes[2].value, "0:1");
+
+    // round 3 evens the score and empties the hands, the series ends in a tie
+    let res = play(&mut deps, GameMove::Scissors {}, GameMove::Paper {});
+    assert_eq!(res.attributes[1].value, "Tie");
+    assert_eq!(query_balance(deps.as_ref(), "host"), coins(50, "token"));
+    assert_eq!(query_balance(deps.as_ref(), "opponent"), coins(50, "token"));
+}
+
+#[test]
+fn minus_one_plays_the_kept_moves() {
+    let mut deps = mock_dependencies();
+
+    let propose = ExecuteMsg::ProposeGame {
+        opponent: Addr::unchecked("opponent"),
+        terms: GameTerms {
+            wager: Some(coin(50, "token")),
+            ruleset: Ruleset::MinusOne {},
+            best_of: 1,
+            timeout: None,
+        },
+    };
+    let accept = ExecuteMsg::AcceptChallenge {
+        host: Addr::unchecked("host"),
+    };
+    let start = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>| {
+        let info = mock_info("host", &coins(50, "token"));
+        execute(deps.as_mut(), mock_env(), info, propose.clone()).unwrap();
+        let info = mock_info("opponent", &coins(50, "token"));
+        execute(deps.as_mut(), mock_env(), info, accept.clone()).unwrap();
+    };
+    let commit_moves = |first: GameMove, second: GameMove| ExecuteMsg::CommitMinusOne {
+        host: Addr::unchecked("host"),
+        opponent: Addr::unchecked("opponent"),
+        commitment: moves_commitment(&(first, second), "salt"),
+    };
+    let reveal_moves = |first: GameMove, second: GameMove| ExecuteMsg::RevealMinusOne {
+        host: Addr::unchecked("host"),
+        opponent: Addr::unchecked("opponent"),
+        moves: (first, second),
+        salt: "salt".to_string(),
+    };
+    let commit_withdrawal = |withdrawn: GameMove| ExecuteMsg::CommitWithdrawal {
+        host: Addr::unchecked("host"),
+        opponent: Addr::unchecked("opponent"),
+        commitment: move_commitment(&withdrawn, "salt"),
+    };
+    let reveal_withdrawal = |withdrawn: GameMove| ExecuteMsg::RevealWithdrawal {
+        host: Addr::unchecked("host"),
+        opponent: Addr::unchecked("opponent"),
+        withdrawn,
+        salt: "salt".to_string(),
+    };
+    start(&mut deps);
+
+    // the classic commit isn't part of this ruleset
+    let info = mock_info("host", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        commit(GameMove::Rock {}, "salt"),
+    );
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::RulesetMismatch {});
+
+    let msg = commit_moves(GameMove::Rock {}, GameMove::Paper {});
+    execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg).unwrap();
+    // betting closes with the first commitment
+    let info = mock_info("alice", &coins(10, "token"));
+    let msg = place_bet(GameResult::HostWins {});
+    let err = execute(deps.as_mut(), mock_env(), info, msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::BettingClosed {});
+    let msg = reveal_moves(GameMove::Rock {}, GameMove::Paper {});
+    let err = execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        *err_unwrapped,
+        ContractError::WrongMinusOnePhase {
+            phase: MinusOnePhase::CommitMoves
+        }
+    );
+    let msg = commit_moves(GameMove::Scissors {}, GameMove::Paper {});
+    let res = execute(deps.as_mut(), mock_env(), mock_info("opponent", &[]), msg).unwrap();
+    assert!(res.attributes.contains(&attr("phase", "reveal_moves")));
+
+    // both pairs are revealed, then each player picks the move to withdraw
+    let msg = reveal_moves(GameMove::Rock {}, GameMove::Paper {});
+    execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg).unwrap();
+    let msg = reveal_moves(GameMove::Scissors {}, GameMove::Paper {});
+    execute(deps.as_mut(), mock_env(), mock_info("opponent", &[]), msg).unwrap();
+    let game = games()
+        .load(
+            &deps.storage,
+            (Addr::unchecked("host"), Addr::unchecked("opponent")),
+        )
+        .unwrap();
+    let minus_one = game.minus_one.unwrap();
+    assert_eq!(minus_one.phase, MinusOnePhase::CommitWithdrawal);
+    assert_eq!(
+        minus_one.opponent.moves,
+        Some((GameMove::Scissors {}, GameMove::Paper {}))
+    );
+
+    let msg = commit_withdrawal(GameMove::Paper {});
+    execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg).unwrap();
+    let msg = commit_withdrawal(GameMove::Rock {});
+    execute(deps.as_mut(), mock_env(), mock_info("opponent", &[]), msg).unwrap();
+    let msg = reveal_withdrawal(GameMove::Rock {});
+    let err = execute(deps.as_mut(), mock_env(), mock_info("opponent", &[]), msg);
+    let err_unwrapped = err.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(*err_unwrapped, ContractError::WithdrawnMoveNotShown {});
+
+    // the opponent can't withdraw and forfeits, the host kept rock
+    let msg = reveal_withdrawal(GameMove::Paper {});
+    execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg).unwrap();
+    let expire = ExecuteMsg::ExpireGame {
+        host: Addr::unchecked("host"),
+        opponent: Addr::unchecked("opponent"),
+    };
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(24 * 60 * 60 + 1);
+    let res = execute(deps.as_mut(), env, mock_info("anyone", &[]), expire).unwrap();
+    assert!(res.attributes.contains(&attr("game_result", "Host won")));
+    assert_eq!(query_balance(deps.as_ref(), "host"), coins(100, "token"));
+
+    // a fair round: scissors kept against paper
+    start(&mut deps);
+    let msg = commit_moves(GameMove::Rock {}, GameMove::Scissors {});
+    execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg).unwrap();
+    let msg = commit_moves(GameMove::Paper {}, GameMove::Paper {});
+    execute(deps.as_mut(), mock_env(), mock_info("opponent", &[]), msg).unwrap();
+    let msg = reveal_moves(GameMove::Rock {}, GameMove::Scissors {});
+    execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg).unwrap();
+    let msg = reveal_moves(GameMove::Paper {}, GameMove::Paper {});
+    execute(deps.as_mut(), mock_env(), mock_info("opponent", &[]), msg).unwrap();
+    let msg = commit_withdrawal(GameMove::Rock {});
+    execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg).unwrap();
+    let msg = commit_withdrawal(GameMove::Paper {});
+    execute(deps.as_mut(), mock_env(), mock_info("opponent", &[]), msg).unwrap();
+    let msg = reveal_withdrawal(GameMove::Paper {});
+    execute(deps.as_mut(), mock_env(), mock_info("opponent", &[]), msg).unwrap();
+    let msg = reveal_withdrawal(GameMove::Rock {});
+    let res = execute(deps.as_mut(), mock_env(), mock_info("host", &[]), msg).unwrap();
+    assert!(res.attributes.contains(&attr("game_result", "Host won")));
+    assert_eq!(query_balance(deps.as_ref(), "host"), coins(200, "token"));
+}